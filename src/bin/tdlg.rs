@@ -0,0 +1,468 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use tdlg::{
+    formats::{LdtkExporter, TiledExporter, TiledFormat},
+    generation::{GenerationError, GenerationReport, GeneratorConfig, SeedVersion},
+    loading::RoomPaths,
+    map::{cells::Coordinate, Room, TopDownMap},
+    render::{AnsiRenderer, SvgRenderer},
+};
+use walkdir::WalkDir;
+
+#[derive(Parser)]
+#[command(name = "tdlg", version, about = "Generate and inspect top-down maps")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a map and print it to the terminal
+    Print {
+        #[command(flatten)]
+        map: MapArgs,
+        /// Colour the map with ANSI escape codes
+        #[arg(long)]
+        color: bool,
+        /// Only print the area around this coordinate, written as x,y
+        #[arg(long, value_parser = parse_coordinate)]
+        center: Option<Coordinate>,
+        #[arg(long, default_value_t = 40)]
+        width: u16,
+        #[arg(long, default_value_t = 20)]
+        height: u16,
+    },
+    /// Generate a map and write it to a file
+    Export {
+        #[command(flatten)]
+        map: MapArgs,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Text)]
+        format: ExportFormat,
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Generate a map and print statistics about how it was generated
+    Stats {
+        #[command(flatten)]
+        map: MapArgs,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check that every file in the template directories is a usable room
+    ValidateTemplates {
+        #[arg(required = true)]
+        directories: Vec<PathBuf>,
+        #[arg(long)]
+        grid_size: Option<u16>,
+    },
+}
+
+#[derive(Args)]
+struct MapArgs {
+    /// A .toml, .json or .ron generator config, flags override its values
+    #[arg(long, short)]
+    config: Option<PathBuf>,
+    #[arg(long, short)]
+    seed: Option<String>,
+    #[arg(long, value_enum)]
+    seed_version: Option<SeedVersionArg>,
+    #[arg(long)]
+    size: Option<u16>,
+    #[arg(long)]
+    rooms: Option<u16>,
+    #[arg(long = "templates")]
+    template_directories: Vec<PathBuf>,
+    /// Surround the map with a wall, --outer-wall=false turns off a config's wall
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    outer_wall: Option<bool>,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum SeedVersionArg {
+    V1,
+    V2,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ExportFormat {
+    Text,
+    Json,
+    Svg,
+    Tmx,
+    Tmj,
+    Ldtk,
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl MapArgs {
+    fn config(&self) -> Result<GeneratorConfig, GenerationError> {
+        let config = match &self.config {
+            Some(path) => GeneratorConfig::load(path)?,
+            None => GeneratorConfig::default(),
+        };
+
+        Ok(self.merge(config))
+    }
+
+    fn merge(&self, mut config: GeneratorConfig) -> GeneratorConfig {
+        if let Some(seed) = &self.seed {
+            config.seed = seed.clone();
+        }
+        if let Some(seed_version) = self.seed_version {
+            config.seed_version = match seed_version {
+                SeedVersionArg::V1 => SeedVersion::V1,
+                SeedVersionArg::V2 => SeedVersion::V2,
+            };
+        }
+        if let Some(size) = self.size {
+            config.grid_size = size;
+        }
+        if let Some(rooms) = self.rooms {
+            config.target_number_rooms = rooms;
+        }
+        if !self.template_directories.is_empty() {
+            config.room_paths = self
+                .template_directories
+                .iter()
+                .map(|directory| RoomPaths {
+                    name: directory.display().to_string(),
+                    template_path: directory.display().to_string(),
+                })
+                .collect();
+        }
+        if let Some(outer_wall) = self.outer_wall {
+            config.include_outer_wall = outer_wall;
+        }
+
+        config
+    }
+
+    fn generate(&self) -> Result<TopDownMap, GenerationError> {
+        self.config()?.generator()?.generate_top_down_map()
+    }
+}
+
+fn main() -> ExitCode {
+    exit_code(run(Cli::parse().command))
+}
+
+fn exit_code(result: Result<(), GenerationError>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error.message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), GenerationError> {
+    match command {
+        Command::Print {
+            map,
+            color,
+            center,
+            width,
+            height,
+        } => map.generate().map(|map| {
+            let renderer = if color {
+                AnsiRenderer::default()
+            } else {
+                AnsiRenderer::plain()
+            };
+
+            match center {
+                Some(center) => {
+                    print!("{}", renderer.render_viewport(&map, &center, width, height))
+                }
+                None => print!("{}", renderer.render(&map)),
+            }
+        }),
+        Command::Export {
+            map,
+            format,
+            output,
+        } => map.generate().and_then(|map| export(&map, format, &output)),
+        Command::Stats { map, json } => map
+            .config()
+            .and_then(|config| config.generator())
+            .and_then(|mut generator| generator.generate_with_report())
+            .map(|(_, report)| print_stats(&report, json)),
+        Command::ValidateTemplates {
+            directories,
+            grid_size,
+        } => validate_templates(&directories, grid_size),
+    }
+}
+
+fn parse_coordinate(value: &str) -> Result<Coordinate, String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("{} is not written as x,y", value))?;
+    let x: i32 = x
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} is not a number", x))?;
+    let y: i32 = y
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} is not a number", y))?;
+
+    Ok(Coordinate::from((x, y)))
+}
+
+fn export(map: &TopDownMap, format: ExportFormat, output: &Path) -> Result<(), GenerationError> {
+    let content = match format {
+        ExportFormat::Text => map.grid().top_layer_display(),
+        ExportFormat::Json => serde_json::to_string_pretty(map).unwrap(),
+        ExportFormat::Svg => SvgRenderer::default().render(map),
+        ExportFormat::Tmx => TiledExporter::default().export(map, TiledFormat::Tmx),
+        ExportFormat::Tmj => TiledExporter::default().export(map, TiledFormat::Tmj),
+        ExportFormat::Ldtk => LdtkExporter::default().export(map),
+        #[cfg(feature = "png")]
+        ExportFormat::Png => {
+            return tdlg::render::PngRenderer::default()
+                .with_path(Some(tdlg::render::Color::Rgb(70, 200, 230)))
+                .save(map, output)
+        }
+    };
+
+    fs::write(output, content).map_err(|error| {
+        GenerationError::map_cannot_be_written(&format!("{}: {}", output.display(), error))
+    })
+}
+
+fn print_stats(report: &GenerationReport, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report).unwrap());
+        return;
+    }
+
+    println!("seed: {}", report.seed);
+    println!(
+        "rooms: {} ({} pre-placed, {} rejected for overlapping)",
+        report.room_count, report.pre_placed_rooms, report.rejected_overlap
+    );
+    for (index, template) in report.templates.iter().enumerate() {
+        println!(
+            "  template {}: {} placed of {} attempts",
+            index, template.placed, template.attempts
+        );
+    }
+    for (name, items) in [
+        ("hidden items", &report.hidden_items),
+        ("items", &report.items),
+    ] {
+        println!(
+            "{}: {} placed of {} requested ({} missed rolls)",
+            name, items.placed, items.requested, items.missed_rolls
+        );
+        for layer_count in items.placed_by_type.iter() {
+            println!("  {:?}: {}", layer_count.layer_type, layer_count.count);
+        }
+    }
+    match report.path_length {
+        Some(path_length) => println!("path length: {}", path_length),
+        None => println!("path length: unreachable"),
+    }
+    println!("exit attempts: {}", report.exit_attempts);
+    println!("locks: {}", report.locks_placed);
+    println!("torches: {}", report.torches_placed);
+    println!("walkable: {:.1}%", report.walkable_percentage);
+    println!("regions: {}", report.region_count);
+    for timing in report.phase_timings.iter() {
+        println!("  {:?}: {:?}", timing.phase, timing.duration);
+    }
+    println!("total: {:?}", report.total_duration);
+}
+
+fn validate_templates(
+    directories: &[PathBuf],
+    grid_size: Option<u16>,
+) -> Result<(), GenerationError> {
+    let mut problems = 0;
+
+    for directory in directories {
+        let mut rooms = 0;
+
+        for entry in WalkDir::new(directory)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_file())
+        {
+            let path = entry.path().display();
+            let content = match fs::read_to_string(entry.path()) {
+                Ok(it) => it,
+                Err(error) => {
+                    println!("{}: cannot be read: {}", path, error);
+                    problems += 1;
+                    continue;
+                }
+            };
+
+            rooms += 1;
+            let room = match Room::from_template(&content) {
+                Ok(it) => it,
+                Err(reason) => {
+                    println!("{}: has a bad door policy header: {}", path, reason);
+                    problems += 1;
+                    continue;
+                }
+            };
+
+            if room.cells().is_empty() {
+                println!("{}: has no cells", path);
+                problems += 1;
+            } else if room.possible_door_cells().is_empty() && !room.has_doors() {
+                println!("{}: has no wall a door could be placed in", path);
+                problems += 1;
+            } else if grid_size.is_some_and(|size| room.max_side_length() + 1 >= size) {
+                println!(
+                    "{}: is {} cells across, too large for the grid",
+                    path,
+                    room.max_side_length()
+                );
+                problems += 1;
+            }
+        }
+
+        println!("{}: {} templates", directory.display(), rooms);
+        if rooms == 0 {
+            problems += 1;
+        }
+    }
+
+    if problems > 0 {
+        return Err(GenerationError::room_templates_cannot_be_loaded());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process::ExitCode};
+
+    use clap::Parser;
+    use tdlg::{
+        generation::{GeneratorConfig, SeedVersion},
+        map::cells::Coordinate,
+    };
+
+    use super::{exit_code, parse_coordinate, run, Cli, Command, MapArgs};
+
+    fn parse(args: &[&str]) -> Command {
+        Cli::try_parse_from([&["tdlg"], args].concat())
+            .unwrap()
+            .command
+    }
+
+    fn map_args(args: &[&str]) -> MapArgs {
+        match parse(&[&["stats"], args].concat()) {
+            Command::Stats { map, .. } => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn flags_override_the_config() {
+        let config = GeneratorConfig {
+            seed: "from config".to_string(),
+            grid_size: 50,
+            target_number_rooms: 10,
+            include_outer_wall: true,
+            ..GeneratorConfig::default()
+        };
+
+        let untouched = map_args(&[]).merge(config.clone());
+        assert_eq!(untouched.seed, "from config");
+        assert_eq!(untouched.grid_size, 50);
+        assert!(untouched.include_outer_wall);
+
+        let merged = map_args(&[
+            "--seed",
+            "from flags",
+            "--seed-version",
+            "v1",
+            "--size",
+            "30",
+            "--rooms",
+            "5",
+            "--templates",
+            "assets/config/rooms",
+            "--outer-wall=false",
+        ])
+        .merge(config);
+        assert_eq!(merged.seed, "from flags");
+        assert_eq!(merged.seed_version, SeedVersion::V1);
+        assert_eq!(merged.grid_size, 30);
+        assert_eq!(merged.target_number_rooms, 5);
+        assert_eq!(merged.room_paths.len(), 1);
+        assert_eq!(merged.room_paths[0].template_path, "assets/config/rooms");
+        assert!(!merged.include_outer_wall);
+    }
+
+    #[test]
+    fn outer_wall_flag_takes_an_optional_value() {
+        for (args, expected) in [
+            (vec![], None),
+            (vec!["--outer-wall"], Some(true)),
+            (vec!["--outer-wall=true"], Some(true)),
+            (vec!["--outer-wall=false"], Some(false)),
+        ] {
+            assert_eq!(map_args(&args).outer_wall, expected, "{:?}", args);
+        }
+
+        // The value has to be attached, so the next argument isn't swallowed.
+        assert!(Cli::try_parse_from(["tdlg", "stats", "--outer-wall", "false"]).is_err());
+    }
+
+    #[test]
+    fn coordinates_are_written_as_x_comma_y() {
+        assert_eq!(parse_coordinate("3,-4"), Ok(Coordinate::from((3, -4))));
+        assert_eq!(parse_coordinate(" 3 , 4 "), Ok(Coordinate::from((3, 4))));
+        assert!(parse_coordinate("3").is_err());
+        assert!(parse_coordinate("a,4").is_err());
+        assert!(parse_coordinate("3,4,5").is_err());
+    }
+
+    #[test]
+    fn invalid_input_is_an_error() {
+        let empty = env::temp_dir().join("tdlg-cli-empty-templates");
+        fs::create_dir_all(&empty).unwrap();
+        let bad_header = env::temp_dir().join("tdlg-cli-bad-header");
+        fs::create_dir_all(&bad_header).unwrap();
+        fs::write(bad_header.join("room.txt"), "@doors = 2\n||||\n|==d\n||||").unwrap();
+
+        assert_eq!(
+            exit_code(run(parse(&["validate-templates", "assets/config/rooms"]))),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(
+            exit_code(run(parse(&["stats", "--size", "0"]))),
+            ExitCode::FAILURE
+        );
+        assert!(run(parse(&[
+            "validate-templates",
+            "assets/config/rooms",
+            "--grid-size",
+            "3"
+        ]))
+        .is_err());
+        assert!(run(parse(&["validate-templates", empty.to_str().unwrap()])).is_err());
+        assert!(run(parse(&["validate-templates", bad_header.to_str().unwrap()])).is_err());
+        assert!(run(parse(&[
+            "stats",
+            "--config",
+            PathBuf::from("missing.toml").to_str().unwrap()
+        ]))
+        .is_err());
+    }
+}
@@ -0,0 +1,741 @@
+use std::{fs, num::NonZeroU16, path::Path};
+
+use serde_json::{json, Value};
+
+use crate::{
+    generation::GenerationError,
+    map::{
+        cells::{Cell, Coordinate},
+        layers::{ItemRarity, LayerType, StructureType},
+        TopDownMap,
+    },
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LdtkCategory {
+    Floor,
+    Wall,
+    Door,
+    Obstacle,
+}
+
+impl LdtkCategory {
+    const ALL: [LdtkCategory; 4] = [
+        LdtkCategory::Floor,
+        LdtkCategory::Wall,
+        LdtkCategory::Door,
+        LdtkCategory::Obstacle,
+    ];
+
+    pub fn of(cell: &Cell) -> Option<Self> {
+        if cell.contains_door() {
+            return Some(LdtkCategory::Door);
+        }
+
+        cell.layers().iter().rev().find_map(|layer| match layer {
+            LayerType::Structure(StructureType::Wall) => Some(LdtkCategory::Wall),
+            LayerType::Structure(StructureType::Torch) => None,
+            LayerType::Structure(_) if layer.is_walkable() => Some(LdtkCategory::Floor),
+            LayerType::Structure(_) => Some(LdtkCategory::Obstacle),
+            LayerType::Floor(_) => Some(LdtkCategory::Floor),
+            _ => None,
+        })
+    }
+
+    pub fn value(&self) -> i64 {
+        match self {
+            LdtkCategory::Floor => 1,
+            LdtkCategory::Wall => 2,
+            LdtkCategory::Door => 3,
+            LdtkCategory::Obstacle => 4,
+        }
+    }
+
+    fn identifier(&self) -> &'static str {
+        match self {
+            LdtkCategory::Floor => "floor",
+            LdtkCategory::Wall => "wall",
+            LdtkCategory::Door => "door",
+            LdtkCategory::Obstacle => "obstacle",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            LdtkCategory::Floor => "#3A5E30",
+            LdtkCategory::Wall => "#AAAAAA",
+            LdtkCategory::Door => "#C88228",
+            LdtkCategory::Obstacle => "#6E6E6E",
+        }
+    }
+}
+
+const LDTK_VERSION: &str = "1.5.3";
+
+// Definition uids, LDtk only needs them to be unique within the project.
+const ENTITIES_LAYER_UID: i64 = 1;
+const COLLISION_LAYER_UID: i64 = 2;
+const ENTRANCE_UID: i64 = 10;
+const EXIT_UID: i64 = 11;
+const ITEM_UID: i64 = 12;
+const KEY_UID: i64 = 13;
+const TABLE_UID: i64 = 14;
+const ROOM_UID: i64 = 15;
+const TORCH_UID: i64 = 16;
+const RARITY_FIELD_UID: i64 = 20;
+const HIDDEN_FIELD_UID: i64 = 21;
+const ROOM_INDEX_FIELD_UID: i64 = 22;
+const LEVEL_UID: i64 = 30;
+const NEXT_UID: i64 = 31;
+
+#[derive(Clone, Debug)]
+pub struct LdtkExporter {
+    grid_size: NonZeroU16,
+    level_name: String,
+}
+
+impl Default for LdtkExporter {
+    fn default() -> Self {
+        Self {
+            grid_size: NonZeroU16::new(16).unwrap(),
+            level_name: "Level_0".to_string(),
+        }
+    }
+}
+
+impl LdtkExporter {
+    pub fn with_grid_size(mut self, grid_size: NonZeroU16) -> Self {
+        self.grid_size = grid_size;
+        self
+    }
+
+    pub fn with_level_name(mut self, level_name: &str) -> Self {
+        self.level_name = level_name.to_string();
+        self
+    }
+
+    pub fn export(&self, map: &TopDownMap) -> String {
+        serde_json::to_string_pretty(&self.project(map)).unwrap()
+    }
+
+    pub fn save(&self, map: &TopDownMap, path: impl AsRef<Path>) -> Result<(), GenerationError> {
+        let path = path.as_ref();
+        fs::write(path, self.export(map)).map_err(|error| {
+            GenerationError::map_cannot_be_written(&format!("{}: {}", path.display(), error))
+        })
+    }
+
+    fn project(&self, map: &TopDownMap) -> Value {
+        let grid_size = self.grid_size.get() as i64;
+        let (min, max) = map.grid().bounds();
+        let width = (max.x() - min.x() + 1) as i64;
+        let height = (max.y() - min.y() + 1) as i64;
+
+        // LDtk grids start at the top left, so the top row is max.y.
+        let grid_position = |coordinate: &Coordinate| {
+            (
+                (coordinate.x() - min.x()) as i64,
+                (max.y() - coordinate.y()) as i64,
+            )
+        };
+
+        let mut iids = Iids::default();
+        let mut entities: Vec<Value> = Vec::new();
+        let mut entity = |definition: &EntityDefinition,
+                          coordinate: &Coordinate,
+                          size: (i64, i64),
+                          fields: Vec<Value>| {
+            let (x, y) = grid_position(coordinate);
+            let px = [x * grid_size, y * grid_size];
+            entities.push(json!({
+                "__identifier": definition.identifier,
+                "__grid": [x, y],
+                "__pivot": [0, 0],
+                "__tags": [],
+                "__tile": null,
+                "__smartColor": definition.color,
+                "__worldX": px[0],
+                "__worldY": px[1],
+                "iid": iids.next(),
+                "width": size.0 * grid_size,
+                "height": size.1 * grid_size,
+                "defUid": definition.uid,
+                "px": px,
+                "fieldInstances": fields,
+            }));
+        };
+
+        entity(&ENTRANCE, map.entry(), (1, 1), Vec::new());
+        entity(&EXIT, map.exit(), (1, 1), Vec::new());
+
+        let mut cells = map.grid().cells();
+        cells.sort_by_key(|cell| grid_position(cell.coordinate()));
+        for cell in cells.iter() {
+            for layer in cell.layers() {
+                let hidden = cell.is_layer_underground(layer) == Some(true);
+                match layer {
+                    LayerType::Item(rarity) => entity(
+                        &ITEM,
+                        cell.coordinate(),
+                        (1, 1),
+                        vec![
+                            field_instance(
+                                "rarity",
+                                "String",
+                                RARITY_FIELD_UID,
+                                json!(rarity_name(rarity)),
+                            ),
+                            field_instance("hidden", "Bool", HIDDEN_FIELD_UID, json!(hidden)),
+                        ],
+                    ),
+                    LayerType::Key => entity(&KEY, cell.coordinate(), (1, 1), Vec::new()),
+                    LayerType::Structure(StructureType::Table) => {
+                        entity(&TABLE, cell.coordinate(), (1, 1), Vec::new())
+                    }
+                    LayerType::Structure(StructureType::Torch) => {
+                        entity(&TORCH, cell.coordinate(), (1, 1), Vec::new())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for (index, room) in map.grid().rooms().iter().enumerate() {
+            let (room_min, room_max) = room.bounds();
+            entity(
+                &ROOM,
+                &Coordinate::from((room_min.x(), room_max.y())),
+                (
+                    (room_max.x() - room_min.x() + 1) as i64,
+                    (room_max.y() - room_min.y() + 1) as i64,
+                ),
+                vec![field_instance(
+                    "index",
+                    "Int",
+                    ROOM_INDEX_FIELD_UID,
+                    json!(index),
+                )],
+            );
+        }
+
+        let mut int_grid: Vec<i64> = vec![0; (width * height) as usize];
+        for cell in cells.iter() {
+            if let Some(category) = LdtkCategory::of(cell) {
+                let (x, y) = grid_position(cell.coordinate());
+                int_grid[(y * width + x) as usize] = category.value();
+            }
+        }
+
+        let level_iid = iids.next();
+        let layer_instance = |identifier: &str, kind: &str, uid: i64, iid: String| {
+            json!({
+                "__identifier": identifier,
+                "__type": kind,
+                "__cWid": width,
+                "__cHei": height,
+                "__gridSize": grid_size,
+                "__opacity": 1,
+                "__pxTotalOffsetX": 0,
+                "__pxTotalOffsetY": 0,
+                "__tilesetDefUid": null,
+                "__tilesetRelPath": null,
+                "iid": iid,
+                "levelId": LEVEL_UID,
+                "layerDefUid": uid,
+                "pxOffsetX": 0,
+                "pxOffsetY": 0,
+                "visible": true,
+                "optionalRules": [],
+                "intGridCsv": [],
+                "autoLayerTiles": [],
+                "seed": 0,
+                "overrideTilesetUid": null,
+                "gridTiles": [],
+                "entityInstances": [],
+            })
+        };
+
+        let mut entities_layer =
+            layer_instance("Entities", "Entities", ENTITIES_LAYER_UID, iids.next());
+        entities_layer["entityInstances"] = json!(entities);
+        let mut collision_layer =
+            layer_instance("Collision", "IntGrid", COLLISION_LAYER_UID, iids.next());
+        collision_layer["intGridCsv"] = json!(int_grid);
+
+        let level = json!({
+            "identifier": self.level_name,
+            "iid": level_iid,
+            "uid": LEVEL_UID,
+            "worldX": 0,
+            "worldY": 0,
+            "worldDepth": 0,
+            "pxWid": width * grid_size,
+            "pxHei": height * grid_size,
+            "__bgColor": "#696A79",
+            "bgColor": null,
+            "useAutoIdentifier": false,
+            "bgRelPath": null,
+            "bgPos": null,
+            "bgPivotX": 0.5,
+            "bgPivotY": 0.5,
+            "__smartColor": "#ADADB5",
+            "__bgPos": null,
+            "externalRelPath": null,
+            "fieldInstances": [],
+            // LDtk lists layers from the top down.
+            "layerInstances": [entities_layer, collision_layer],
+            "__neighbours": [],
+        });
+
+        let int_grid_values: Vec<Value> = LdtkCategory::ALL
+            .iter()
+            .map(|category| {
+                json!({
+                    "value": category.value(),
+                    "identifier": category.identifier(),
+                    "color": category.color(),
+                    "tile": null,
+                    "groupUid": 0,
+                })
+            })
+            .collect();
+        let entity_definitions: Vec<Value> = [ENTRANCE, EXIT, ITEM, KEY, TABLE, TORCH, ROOM]
+            .iter()
+            .map(|definition| definition.to_json(grid_size))
+            .collect();
+        let defs = json!({
+            "layers": [
+                layer_definition("Entities", "Entities", ENTITIES_LAYER_UID, grid_size, Vec::new()),
+                layer_definition("Collision", "IntGrid", COLLISION_LAYER_UID, grid_size, int_grid_values),
+            ],
+            "entities": entity_definitions,
+            "tilesets": [],
+            "enums": [],
+            "externalEnums": [],
+            "levelFields": [],
+        });
+
+        json!({
+            "__header__": {
+                "fileType": "LDtk Project JSON",
+                "app": "LDtk",
+                "doc": "https://ldtk.io/json",
+                "schema": "https://ldtk.io/files/JSON_SCHEMA.json",
+                "appAuthor": "Sebastien 'deepnight' Benard",
+                "appVersion": LDTK_VERSION,
+                "url": "https://ldtk.io",
+            },
+            "iid": iids.next(),
+            "jsonVersion": LDTK_VERSION,
+            "appBuildId": 0,
+            "nextUid": NEXT_UID,
+            "identifierStyle": "Capitalize",
+            "toc": [],
+            "worldLayout": "Free",
+            "worldGridWidth": 256,
+            "worldGridHeight": 256,
+            "defaultLevelWidth": width * grid_size,
+            "defaultLevelHeight": height * grid_size,
+            "defaultPivotX": 0,
+            "defaultPivotY": 0,
+            "defaultGridSize": grid_size,
+            "defaultEntityWidth": grid_size,
+            "defaultEntityHeight": grid_size,
+            "bgColor": "#40465B",
+            "defaultLevelBgColor": "#696A79",
+            "minifyJson": false,
+            "externalLevels": false,
+            "exportTiled": false,
+            "simplifiedExport": false,
+            "imageExportMode": "None",
+            "exportLevelBg": true,
+            "pngFilePattern": null,
+            "backupOnSave": false,
+            "backupLimit": 10,
+            "backupRelPath": null,
+            "levelNamePattern": "Level_%idx",
+            "tutorialDesc": null,
+            "customCommands": [],
+            "flags": [],
+            "defs": defs,
+            "levels": [level],
+            "worlds": [],
+            "dummyWorldIid": iids.next(),
+        })
+    }
+}
+
+fn rarity_name(rarity: &ItemRarity) -> &'static str {
+    match rarity {
+        ItemRarity::Common => "common",
+        ItemRarity::Uncommon => "uncommon",
+        ItemRarity::Rare => "rare",
+        ItemRarity::Exotic => "exotic",
+    }
+}
+
+fn field_instance(identifier: &str, kind: &str, uid: i64, value: Value) -> Value {
+    json!({
+        "__identifier": identifier,
+        "__type": kind,
+        "__value": value,
+        "__tile": null,
+        "defUid": uid,
+        "realEditorValues": [],
+    })
+}
+
+fn layer_definition(
+    identifier: &str,
+    kind: &str,
+    uid: i64,
+    grid_size: i64,
+    int_grid_values: Vec<Value>,
+) -> Value {
+    json!({
+        "__type": kind,
+        "identifier": identifier,
+        "type": kind,
+        "uid": uid,
+        "doc": null,
+        "uiColor": null,
+        "gridSize": grid_size,
+        "guideGridWid": 0,
+        "guideGridHei": 0,
+        "displayOpacity": 1,
+        "inactiveOpacity": 1,
+        "hideInList": false,
+        "hideFieldsWhenInactive": true,
+        "canSelectWhenInactive": true,
+        "renderInWorldView": true,
+        "pxOffsetX": 0,
+        "pxOffsetY": 0,
+        "parallaxFactorX": 0,
+        "parallaxFactorY": 0,
+        "parallaxScaling": true,
+        "requiredTags": [],
+        "excludedTags": [],
+        "autoTilesKilledByOtherLayerUid": null,
+        "uiFilterTags": [],
+        "useAsyncRender": false,
+        "intGridValues": int_grid_values,
+        "intGridValuesGroups": [],
+        "autoRuleGroups": [],
+        "autoSourceLayerDefUid": null,
+        "tilesetDefUid": null,
+        "tilePivotX": 0,
+        "tilePivotY": 0,
+        "biomeFieldUid": null,
+    })
+}
+
+struct FieldDefinition {
+    identifier: &'static str,
+    kind: &'static str,
+    uid: i64,
+}
+
+impl FieldDefinition {
+    fn to_json(&self) -> Value {
+        json!({
+            "identifier": self.identifier,
+            "doc": null,
+            "__type": self.kind,
+            "uid": self.uid,
+            "type": format!("F_{}", self.kind),
+            "isArray": false,
+            "canBeNull": false,
+            "arrayMinLength": null,
+            "arrayMaxLength": null,
+            "editorDisplayMode": "ValueOnly",
+            "editorDisplayScale": 1,
+            "editorDisplayPos": "Above",
+            "editorLinkStyle": "StraightArrow",
+            "editorDisplayColor": null,
+            "editorAlwaysShow": false,
+            "editorShowInWorld": true,
+            "editorCutLongValues": true,
+            "editorTextSuffix": null,
+            "editorTextPrefix": null,
+            "useForSmartColor": false,
+            "exportToToc": false,
+            "searchable": false,
+            "min": null,
+            "max": null,
+            "regex": null,
+            "acceptFileTypes": null,
+            "defaultOverride": null,
+            "textLanguageMode": null,
+            "symmetricalRef": false,
+            "autoChainRef": true,
+            "allowOutOfLevelRef": true,
+            "allowedRefs": "OnlySame",
+            "allowedRefsEntityUid": null,
+            "allowedRefTags": [],
+            "tilesetUid": null,
+        })
+    }
+}
+
+struct EntityDefinition {
+    identifier: &'static str,
+    uid: i64,
+    color: &'static str,
+    resizable: bool,
+    hollow: bool,
+    fields: &'static [FieldDefinition],
+}
+
+impl EntityDefinition {
+    fn to_json(&self, grid_size: i64) -> Value {
+        json!({
+            "identifier": self.identifier,
+            "uid": self.uid,
+            "tags": [],
+            "exportToToc": false,
+            "allowOutOfBounds": false,
+            "doc": null,
+            "width": grid_size,
+            "height": grid_size,
+            "resizableX": self.resizable,
+            "resizableY": self.resizable,
+            "minWidth": null,
+            "maxWidth": null,
+            "minHeight": null,
+            "maxHeight": null,
+            "keepAspectRatio": false,
+            "tileOpacity": 1,
+            "fillOpacity": if self.hollow { 0.0 } else { 0.08 },
+            "lineOpacity": 1,
+            "hollow": self.hollow,
+            "color": self.color,
+            "renderMode": "Rectangle",
+            "showName": true,
+            "tilesetId": null,
+            "tileRenderMode": "FitInside",
+            "tileRect": null,
+            "uiTileRect": null,
+            "nineSliceBorders": [],
+            "maxCount": 0,
+            "limitScope": "PerLevel",
+            "limitBehavior": "MoveLastOne",
+            "pivotX": 0,
+            "pivotY": 0,
+            "fieldDefs": self.fields.iter().map(|field| field.to_json()).collect::<Vec<Value>>(),
+        })
+    }
+}
+
+const ENTRANCE: EntityDefinition = EntityDefinition {
+    identifier: "Entrance",
+    uid: ENTRANCE_UID,
+    color: "#3CDC5A",
+    resizable: false,
+    hollow: false,
+    fields: &[],
+};
+
+const EXIT: EntityDefinition = EntityDefinition {
+    identifier: "Exit",
+    uid: EXIT_UID,
+    color: "#DC3232",
+    resizable: false,
+    hollow: false,
+    fields: &[],
+};
+
+const ITEM: EntityDefinition = EntityDefinition {
+    identifier: "Item",
+    uid: ITEM_UID,
+    color: "#E6E6E6",
+    resizable: false,
+    hollow: false,
+    fields: &[
+        FieldDefinition {
+            identifier: "rarity",
+            kind: "String",
+            uid: RARITY_FIELD_UID,
+        },
+        FieldDefinition {
+            identifier: "hidden",
+            kind: "Bool",
+            uid: HIDDEN_FIELD_UID,
+        },
+    ],
+};
+
+const KEY: EntityDefinition = EntityDefinition {
+    identifier: "Key",
+    uid: KEY_UID,
+    color: "#FADC28",
+    resizable: false,
+    hollow: false,
+    fields: &[],
+};
+
+const TABLE: EntityDefinition = EntityDefinition {
+    identifier: "Table",
+    uid: TABLE_UID,
+    color: "#A06428",
+    resizable: false,
+    hollow: false,
+    fields: &[],
+};
+
+const TORCH: EntityDefinition = EntityDefinition {
+    identifier: "Torch",
+    uid: TORCH_UID,
+    color: "#FFA028",
+    resizable: false,
+    hollow: false,
+    fields: &[],
+};
+
+const ROOM: EntityDefinition = EntityDefinition {
+    identifier: "Room",
+    uid: ROOM_UID,
+    color: "#FFFFFF",
+    resizable: true,
+    hollow: true,
+    fields: &[FieldDefinition {
+        identifier: "index",
+        kind: "Int",
+        uid: ROOM_INDEX_FIELD_UID,
+    }],
+};
+
+// LDtk wants UUIDs for iids. Counting them up keeps exports of the same map
+// byte for byte identical.
+#[derive(Default)]
+struct Iids(u64);
+
+impl Iids {
+    fn next(&mut self) -> String {
+        self.0 += 1;
+        format!("7d1a6c40-0000-4000-8000-{:012x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde_json::Value;
+
+    use crate::{
+        generation::{ItemChance, ItemGeneration},
+        map::{
+            cells::{Cell, Coordinate},
+            layers::{FloorType, ItemRarity, LayerType, StructureType},
+            TopDownMap,
+        },
+        test_support::map_with,
+    };
+
+    use super::{LdtkCategory, LdtkExporter};
+
+    fn map() -> TopDownMap {
+        map_with("ldtk", 30, |builder| {
+            builder.target_items(ItemGeneration {
+                target_num_items: 8,
+                item_ranges: vec![ItemChance {
+                    layer_type: LayerType::Item(ItemRarity::Uncommon),
+                    chance: 0..100,
+                }],
+            });
+        })
+    }
+
+    #[test]
+    fn project_references_its_own_definitions() {
+        let map = map();
+        let project: Value = serde_json::from_str(&LdtkExporter::default().export(&map)).unwrap();
+
+        let defs = &project["defs"];
+        let layer_uids: HashSet<i64> = defs["layers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|layer| layer["uid"].as_i64().unwrap())
+            .collect();
+        let entity_uids: HashSet<i64> = defs["entities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entity| entity["uid"].as_i64().unwrap())
+            .collect();
+
+        let layers = project["levels"][0]["layerInstances"].as_array().unwrap();
+        for layer in layers {
+            assert!(layer_uids.contains(&layer["layerDefUid"].as_i64().unwrap()));
+        }
+
+        let entities = layers[0]["entityInstances"].as_array().unwrap();
+        for entity in entities {
+            assert!(entity_uids.contains(&entity["defUid"].as_i64().unwrap()));
+        }
+
+        let count = |identifier: &str| {
+            entities
+                .iter()
+                .filter(|entity| entity["__identifier"] == identifier)
+                .count()
+        };
+        assert_eq!(count("Entrance"), 1);
+        assert_eq!(count("Exit"), 1);
+        assert_eq!(count("Room"), map.grid().rooms().len());
+        assert_eq!(count("Item"), 8);
+
+        // The outer wall puts the top left corner of the map at (-1, 30).
+        let entrance = entities
+            .iter()
+            .find(|entity| entity["__identifier"] == "Entrance")
+            .unwrap();
+        assert_eq!(
+            entrance["__grid"],
+            serde_json::json!([map.entry().x() + 1, 30 - map.entry().y()])
+        );
+    }
+
+    #[test]
+    fn int_grid_follows_cell_categories() {
+        let map = map();
+        let project: Value = serde_json::from_str(&LdtkExporter::default().export(&map)).unwrap();
+        let int_grid = project["levels"][0]["layerInstances"][1]["intGridCsv"]
+            .as_array()
+            .unwrap();
+
+        assert_eq!(int_grid.len(), 32 * 32);
+        let doors = map
+            .grid()
+            .cells()
+            .into_iter()
+            .filter(|cell| LdtkCategory::of(cell) == Some(LdtkCategory::Door))
+            .count();
+        assert!(doors > 0);
+        assert_eq!(
+            int_grid
+                .iter()
+                .filter(|value| value.as_i64() == Some(LdtkCategory::Door.value()))
+                .count(),
+            doors
+        );
+        assert_eq!(int_grid[0].as_i64(), Some(LdtkCategory::Obstacle.value()));
+
+        let on_floor = |structure_type| {
+            LdtkCategory::of(&Cell::from((
+                Coordinate::from(0),
+                vec![
+                    LayerType::Floor(FloorType::Outdoor),
+                    LayerType::Structure(structure_type),
+                ],
+            )))
+        };
+        assert_eq!(on_floor(StructureType::Rubble), Some(LdtkCategory::Floor));
+        assert_eq!(
+            on_floor(StructureType::Boulder),
+            Some(LdtkCategory::Obstacle)
+        );
+        assert_eq!(on_floor(StructureType::Table), Some(LdtkCategory::Obstacle));
+    }
+}
@@ -0,0 +1,5 @@
+mod ldtk;
+mod tiled;
+
+pub use ldtk::{LdtkCategory, LdtkExporter};
+pub use tiled::{TileMapping, TiledExporter, TiledFormat, TiledImporter};
@@ -0,0 +1,586 @@
+mod model;
+mod tile_mapping;
+mod tmx;
+
+use std::{fs, num::NonZeroU16, path::Path};
+
+use crate::{
+    generation::GenerationError,
+    map::{
+        cells::{Cell, Coordinate},
+        layers::{LayerType, StructureType},
+        Grid, TopDownMap,
+    },
+};
+
+use model::{Layer, Object, ObjectGroup, Point, Property, TileLayer, TiledMap, Tileset};
+
+pub use tile_mapping::TileMapping;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TiledFormat {
+    Tmx,
+    Tmj,
+}
+
+impl TiledFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "tmx" => Some(TiledFormat::Tmx),
+            "tmj" | "json" => Some(TiledFormat::Tmj),
+            _ => None,
+        }
+    }
+}
+
+// Tile layers from the bottom of a cell's layer stack to the top, the
+// importer rebuilds cells by stacking them back up in this order. Torches
+// hang on walls, so they get a layer of their own above the structures.
+const HIDDEN: &str = "hidden";
+const FLOOR: &str = "floor";
+const STRUCTURES: &str = "structures";
+const DECORATIONS: &str = "decorations";
+const ITEMS: &str = "items";
+const PATH: &str = "path";
+const TILE_LAYERS: [&str; 6] = [HIDDEN, FLOOR, STRUCTURES, DECORATIONS, ITEMS, PATH];
+const OBJECTS: &str = "objects";
+
+const ORIGIN_X: &str = "origin_x";
+const ORIGIN_Y: &str = "origin_y";
+const GRID_SIZE: &str = "grid_size";
+
+fn tile_layer_for(cell: &Cell, name: &str) -> Option<LayerType> {
+    let layers = cell.layers();
+    let is_item =
+        |layer: &LayerType| matches!(layer, LayerType::Item(_) | LayerType::Key | LayerType::Note);
+    let buried = |layer: &LayerType| cell.is_layer_underground(layer) == Some(true);
+    let torch = LayerType::Structure(StructureType::Torch);
+
+    match name {
+        HIDDEN => layers
+            .iter()
+            .find(|layer| is_item(layer) && buried(layer))
+            .copied(),
+        FLOOR => layers
+            .iter()
+            .find(|layer| matches!(layer, LayerType::Floor(_)))
+            .copied(),
+        STRUCTURES => layers
+            .iter()
+            .rev()
+            .find(|layer| matches!(layer, LayerType::Structure(_)) && **layer != torch)
+            .copied(),
+        DECORATIONS => layers.iter().find(|layer| **layer == torch).copied(),
+        ITEMS => layers
+            .iter()
+            .rev()
+            .find(|layer| is_item(layer) && !buried(layer))
+            .copied(),
+        PATH => layers
+            .iter()
+            .find(|layer| **layer == LayerType::Path)
+            .copied(),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TiledExporter {
+    mapping: TileMapping,
+    tile_size: NonZeroU16,
+    tileset_source: Option<String>,
+}
+
+impl Default for TiledExporter {
+    fn default() -> Self {
+        Self {
+            mapping: TileMapping::default(),
+            tile_size: NonZeroU16::new(16).unwrap(),
+            tileset_source: None,
+        }
+    }
+}
+
+impl TiledExporter {
+    pub fn with_mapping(mut self, mapping: TileMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    pub fn with_tile_size(mut self, tile_size: NonZeroU16) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn with_tileset_source(mut self, tileset_source: &str) -> Self {
+        self.tileset_source = Some(tileset_source.to_string());
+        self
+    }
+
+    pub fn export(&self, map: &TopDownMap, format: TiledFormat) -> String {
+        let tiled_map = self.tiled_map(map);
+
+        match format {
+            TiledFormat::Tmx => tmx::write(&tiled_map),
+            TiledFormat::Tmj => serde_json::to_string_pretty(&tiled_map).unwrap(),
+        }
+    }
+
+    pub fn save(&self, map: &TopDownMap, path: impl AsRef<Path>) -> Result<(), GenerationError> {
+        let path = path.as_ref();
+        let format = TiledFormat::from_path(path).ok_or_else(|| {
+            GenerationError::map_cannot_be_written(&format!(
+                "{} is not a .tmx or .tmj file",
+                path.display()
+            ))
+        })?;
+
+        fs::write(path, self.export(map, format)).map_err(|error| {
+            GenerationError::map_cannot_be_written(&format!("{}: {}", path.display(), error))
+        })
+    }
+
+    fn tiled_map(&self, map: &TopDownMap) -> TiledMap {
+        let (min, max) = map.grid().bounds();
+        let width = (max.x() - min.x() + 1) as u32;
+        let height = (max.y() - min.y() + 1) as u32;
+        let tile_size = self.tile_size.get() as u32;
+
+        // Tiled's rows run top to bottom, so row 0 is the top of the map.
+        let coordinate_at = |index: u32| {
+            Coordinate::from((
+                min.x() + (index % width) as i32,
+                max.y() - (index / width) as i32,
+            ))
+        };
+        let pixel = |coordinate: &Coordinate| Point {
+            x: ((coordinate.x() - min.x()) as u32 * tile_size) as f64,
+            y: ((max.y() - coordinate.y()) as u32 * tile_size) as f64,
+        };
+        let center = |coordinate: &Coordinate| {
+            let point = pixel(coordinate);
+            Point {
+                x: point.x + tile_size as f64 / 2.0,
+                y: point.y + tile_size as f64 / 2.0,
+            }
+        };
+
+        let mut layers: Vec<Layer> = TILE_LAYERS
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                Layer::TileLayer(TileLayer {
+                    id: index as u32 + 1,
+                    name: name.to_string(),
+                    width,
+                    height,
+                    x: 0,
+                    y: 0,
+                    opacity: 1.0,
+                    visible: *name != HIDDEN,
+                    data: (0..width * height)
+                        .map(|index| {
+                            map.grid()
+                                .cell(&coordinate_at(index))
+                                .and_then(|cell| tile_layer_for(cell, name))
+                                .map(|layer_type| self.mapping.tile_id(&layer_type))
+                                .unwrap_or_default()
+                        })
+                        .collect(),
+                })
+            })
+            .collect();
+
+        let mut objects: Vec<Object> = vec![
+            Object {
+                point: true,
+                ..object(1, "entrance", "entrance", center(map.entry()), None)
+            },
+            Object {
+                point: true,
+                ..object(2, "exit", "exit", center(map.exit()), None)
+            },
+        ];
+
+        for (index, room) in map.grid().rooms().iter().enumerate() {
+            let (room_min, room_max) = room.bounds();
+            objects.push(object(
+                objects.len() as u32 + 1,
+                &format!("room {}", index),
+                "room",
+                pixel(&Coordinate::from((room_min.x(), room_max.y()))),
+                Some(Point {
+                    x: ((room_max.x() - room_min.x() + 1) as u32 * tile_size) as f64,
+                    y: ((room_max.y() - room_min.y() + 1) as u32 * tile_size) as f64,
+                }),
+            ));
+        }
+
+        if let Some(path) = map.path().filter(|path| !path.is_empty()) {
+            let start = center(&path[0]);
+            objects.push(Object {
+                polyline: path
+                    .iter()
+                    .map(|coordinate| {
+                        let point = center(coordinate);
+                        Point {
+                            x: point.x - start.x,
+                            y: point.y - start.y,
+                        }
+                    })
+                    .collect(),
+                ..object(objects.len() as u32 + 1, "path", "path", start, None)
+            });
+        }
+
+        let next_object_id = objects.len() as u32 + 1;
+        layers.push(Layer::ObjectGroup(ObjectGroup {
+            id: TILE_LAYERS.len() as u32 + 1,
+            name: OBJECTS.to_string(),
+            x: 0,
+            y: 0,
+            opacity: 1.0,
+            visible: true,
+            draworder: "topdown".to_string(),
+            objects,
+        }));
+
+        let tileset = match &self.tileset_source {
+            Some(source) => Tileset {
+                firstgid: 1,
+                source: Some(source.clone()),
+                name: None,
+                tilewidth: None,
+                tileheight: None,
+                tilecount: None,
+                columns: None,
+            },
+            None => Tileset {
+                firstgid: 1,
+                source: None,
+                name: Some("tdlg".to_string()),
+                tilewidth: Some(tile_size),
+                tileheight: Some(tile_size),
+                tilecount: Some(self.mapping.tile_count()),
+                columns: Some(0),
+            },
+        };
+
+        TiledMap {
+            kind: "map".to_string(),
+            version: "1.10".to_string(),
+            orientation: "orthogonal".to_string(),
+            renderorder: "right-down".to_string(),
+            width,
+            height,
+            tilewidth: tile_size,
+            tileheight: tile_size,
+            infinite: false,
+            nextlayerid: TILE_LAYERS.len() as u32 + 2,
+            nextobjectid: next_object_id,
+            properties: [
+                (ORIGIN_X, min.x() as i64),
+                (ORIGIN_Y, min.y() as i64),
+                (GRID_SIZE, map.grid().size() as i64),
+            ]
+            .into_iter()
+            .map(|(name, value)| Property {
+                name: name.to_string(),
+                kind: "int".to_string(),
+                value,
+            })
+            .collect(),
+            tilesets: vec![tileset],
+            layers,
+        }
+    }
+}
+
+fn object(id: u32, name: &str, kind: &str, position: Point, size: Option<Point>) -> Object {
+    let size = size.unwrap_or(Point { x: 0.0, y: 0.0 });
+    Object {
+        id,
+        name: name.to_string(),
+        kind: kind.to_string(),
+        x: position.x,
+        y: position.y,
+        width: size.x,
+        height: size.y,
+        rotation: 0.0,
+        visible: true,
+        point: false,
+        polyline: Vec::new(),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TiledImporter {
+    mapping: TileMapping,
+}
+
+impl TiledImporter {
+    pub fn with_mapping(mut self, mapping: TileMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    pub fn import(&self, content: &str, format: TiledFormat) -> Result<Grid, GenerationError> {
+        let tiled_map = match format {
+            TiledFormat::Tmx => tmx::read(content)?,
+            TiledFormat::Tmj => serde_json::from_str(content)
+                .map_err(|error| GenerationError::map_cannot_be_read(&error.to_string()))?,
+        };
+
+        self.grid(&tiled_map)
+    }
+
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<Grid, GenerationError> {
+        let path = path.as_ref();
+        let format = TiledFormat::from_path(path).ok_or_else(|| {
+            GenerationError::map_cannot_be_read(&format!(
+                "{} is not a .tmx or .tmj file",
+                path.display()
+            ))
+        })?;
+        let content = fs::read_to_string(path).map_err(|error| {
+            GenerationError::map_cannot_be_read(&format!("{}: {}", path.display(), error))
+        })?;
+
+        self.import(&content, format)
+    }
+
+    fn grid(&self, tiled_map: &TiledMap) -> Result<Grid, GenerationError> {
+        let origin_x = tiled_map.property(ORIGIN_X).unwrap_or_default() as i32;
+        let origin_y = tiled_map.property(ORIGIN_Y).unwrap_or_default() as i32;
+        let grid_size = tiled_map
+            .property(GRID_SIZE)
+            .unwrap_or(tiled_map.width.max(tiled_map.height) as i64);
+        let grid_size = u16::try_from(grid_size).map_err(|_| {
+            GenerationError::map_cannot_be_read(&format!("grid size {} is out of range", grid_size))
+        })?;
+        let top = origin_y + tiled_map.height as i32 - 1;
+        let tile_size = tiled_map.tilewidth.max(1) as f64;
+
+        let coordinate_at = |index: usize| {
+            Coordinate::from((
+                origin_x + (index as u32 % tiled_map.width) as i32,
+                top - (index as u32 / tiled_map.width) as i32,
+            ))
+        };
+        let coordinate_of = |x: f64, y: f64| {
+            Coordinate::from((
+                origin_x + (x / tile_size).floor() as i32,
+                top - (y / tile_size).floor() as i32,
+            ))
+        };
+
+        let first_gid = tiled_map
+            .tilesets
+            .first()
+            .map(|tileset| tileset.firstgid)
+            .unwrap_or(1);
+
+        // The size comes from the file, so every layer is checked against it
+        // before any cells are allocated.
+        let cell_count = tiled_map
+            .width
+            .checked_mul(tiled_map.height)
+            .ok_or_else(|| {
+                GenerationError::map_cannot_be_read(&format!(
+                    "{}x{} is too large",
+                    tiled_map.width, tiled_map.height
+                ))
+            })? as usize;
+        let layers: Vec<(&str, &TileLayer)> = TILE_LAYERS
+            .iter()
+            .filter_map(|name| {
+                tiled_map.layers.iter().find_map(|layer| match layer {
+                    Layer::TileLayer(layer) if layer.name == *name => Some((*name, layer)),
+                    _ => None,
+                })
+            })
+            .collect();
+        if layers.is_empty() {
+            return Err(GenerationError::map_cannot_be_read("no tile layers"));
+        }
+        if let Some((name, _)) = layers
+            .iter()
+            .find(|(_, layer)| layer.width != tiled_map.width || layer.data.len() != cell_count)
+        {
+            return Err(GenerationError::map_cannot_be_read(&format!(
+                "{} layer does not cover the whole map",
+                name
+            )));
+        }
+
+        let mut cells: Vec<Cell> = (0..cell_count)
+            .map(|index| Cell::from(coordinate_at(index)))
+            .collect();
+
+        for (name, layer) in layers {
+            for (cell, tile_id) in cells.iter_mut().zip(layer.data.iter()) {
+                // Tiled keeps flip flags in the top bits of a tile id.
+                let tile_id = tile_id & 0x0fff_ffff;
+                if tile_id == 0 {
+                    continue;
+                }
+
+                let local_id = (tile_id + 1).checked_sub(first_gid).ok_or_else(|| {
+                    GenerationError::map_cannot_be_read(&format!(
+                        "tile {} in the {} layer is below the tileset's first gid {}",
+                        tile_id, name, first_gid
+                    ))
+                })?;
+
+                match self.mapping.layer_type(local_id) {
+                    Some(layer_type) if name == HIDDEN => cell.bury_layer(&layer_type),
+                    Some(layer_type) => cell.add_layer(layer_type),
+                    None => {
+                        return Err(GenerationError::map_cannot_be_read(&format!(
+                            "tile {} in the {} layer has no layer type",
+                            tile_id, name
+                        )))
+                    }
+                }
+            }
+        }
+
+        let mut grid = Grid::build(grid_size, "tiled".to_string());
+        for cell in cells {
+            grid.add_cell(cell);
+        }
+
+        let objects = tiled_map.layers.iter().flat_map(|layer| match layer {
+            Layer::ObjectGroup(group) if group.name == OBJECTS => group.objects.iter(),
+            _ => [].iter(),
+        });
+        for object in objects {
+            let layer_type = match object.kind.as_str() {
+                "entrance" => LayerType::Entrance,
+                "exit" => LayerType::Exit,
+                _ => continue,
+            };
+            grid.add_layer(&coordinate_of(object.x, object.y), layer_type);
+        }
+
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::{ItemChance, ItemGeneration},
+        map::{
+            layers::{ItemRarity, LayerType, StructureType},
+            TopDownMap,
+        },
+        test_support::map_with,
+    };
+
+    use super::{TiledExporter, TiledFormat, TiledImporter};
+
+    fn map() -> TopDownMap {
+        map_with("tiled", 30, |builder| {
+            builder
+                .target_items(ItemGeneration {
+                    target_num_items: 10,
+                    item_ranges: vec![ItemChance {
+                        layer_type: LayerType::Item(ItemRarity::Rare),
+                        chance: 0..100,
+                    }],
+                })
+                .target_num_locks(1)
+                .torch_spacing(NonZeroU16::new(6).unwrap());
+        })
+    }
+
+    #[test]
+    fn exported_maps_import_back_to_the_same_grid() {
+        let map = map();
+        assert!(map.grid().cells().iter().any(|cell| cell
+            .layers()
+            .contains(&LayerType::Structure(StructureType::Torch))));
+
+        for format in [TiledFormat::Tmx, TiledFormat::Tmj] {
+            let content = TiledExporter::default().export(&map, format);
+            let grid = TiledImporter::default().import(&content, format).unwrap();
+
+            assert_eq!(grid.size(), map.grid().size(), "{:?}", format);
+            assert_eq!(grid.cells().len(), map.grid().cells().len(), "{:?}", format);
+            for cell in map.grid().cells() {
+                assert_eq!(
+                    grid.cell(cell.coordinate()).map(|it| it.layers()),
+                    Some(cell.layers()),
+                    "{:?} at {:?}",
+                    format,
+                    cell.coordinate()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tiles_below_the_first_gid_are_rejected() {
+        let content = TiledExporter::default()
+            .export(&map(), TiledFormat::Tmj)
+            .replace(r#""firstgid": 1"#, r#""firstgid": 500"#);
+
+        let error = TiledImporter::default()
+            .import(&content, TiledFormat::Tmj)
+            .unwrap_err();
+        assert!(error.message.starts_with("map_cannot_be_read: tile "));
+        assert!(error.message.contains("below the tileset's first gid 500"));
+    }
+
+    #[test]
+    fn map_sizes_are_checked_before_allocating() {
+        let import = |width: u32, height: u32, data: &str, grid_size: i64| {
+            let content = format!(
+                r#"{{"type":"map","version":"1.10","orientation":"orthogonal","renderorder":"right-down",
+                "width":{width},"height":{height},"tilewidth":16,"tileheight":16,"infinite":false,
+                "nextlayerid":2,"nextobjectid":1,
+                "properties":[{{"name":"grid_size","type":"int","value":{grid_size}}}],
+                "tilesets":[{{"firstgid":1,"source":"tdlg.tsx"}}],
+                "layers":[{{"type":"tilelayer","id":1,"name":"floor","width":{width},"height":{height},
+                "x":0,"y":0,"opacity":1.0,"visible":true,"data":[{data}]}}]}}"#
+            );
+            TiledImporter::default()
+                .import(&content, TiledFormat::Tmj)
+                .map(|grid| grid.cells().len())
+                .map_err(|error| error.message)
+        };
+
+        assert_eq!(import(2, 2, "9,9,9,9", 2), Ok(4));
+        assert_eq!(
+            import(70000, 70000, "9", 2),
+            Err("map_cannot_be_read: 70000x70000 is too large".to_string())
+        );
+        assert_eq!(
+            import(30000, 30000, "9", 2),
+            Err("map_cannot_be_read: floor layer does not cover the whole map".to_string())
+        );
+        assert_eq!(
+            import(2, 2, "9,9,9,9", 70000),
+            Err("map_cannot_be_read: grid size 70000 is out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn tmx_has_separate_layers_and_objects() {
+        let map = map();
+        let tmx = TiledExporter::default()
+            .with_tileset_source("tdlg.tsx")
+            .export(&map, TiledFormat::Tmx);
+
+        for name in ["floor", "structures", "decorations", "items"] {
+            assert!(tmx.contains(&format!(r#"name="{}""#, name)), "{}", name);
+        }
+        assert!(tmx.contains(r#"<tileset firstgid="1" source="tdlg.tsx"/>"#));
+        assert!(tmx.contains(r#"type="entrance""#));
+        assert!(tmx.contains(r#"name="room 0" type="room""#));
+        assert!(tmx.contains("<polyline points=\"0,0 "));
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+// Just enough of Tiled's map format for what tdlg writes and reads back. The
+// field names follow the TMJ JSON format, the TMX writer and reader map the
+// same structs to XML.
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct TiledMap {
+    #[serde(rename = "type", default = "map_type")]
+    pub kind: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub orientation: String,
+    #[serde(default)]
+    pub renderorder: String,
+    pub width: u32,
+    pub height: u32,
+    pub tilewidth: u32,
+    pub tileheight: u32,
+    #[serde(default)]
+    pub infinite: bool,
+    #[serde(default)]
+    pub nextlayerid: u32,
+    #[serde(default)]
+    pub nextobjectid: u32,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+    #[serde(default)]
+    pub tilesets: Vec<Tileset>,
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+}
+
+fn map_type() -> String {
+    "map".to_string()
+}
+
+impl TiledMap {
+    pub fn property(&self, name: &str) -> Option<i64> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| property.value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Property {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Tileset {
+    pub firstgid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilewidth: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tileheight: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilecount: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum Layer {
+    TileLayer(TileLayer),
+    ObjectGroup(ObjectGroup),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct TileLayer {
+    pub id: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default = "one")]
+    pub opacity: f32,
+    #[serde(default = "visible")]
+    pub visible: bool,
+    pub data: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct ObjectGroup {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default = "one")]
+    pub opacity: f32,
+    #[serde(default = "visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub draworder: String,
+    #[serde(default)]
+    pub objects: Vec<Object>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Object {
+    pub id: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub width: f64,
+    #[serde(default)]
+    pub height: f64,
+    #[serde(default)]
+    pub rotation: f64,
+    #[serde(default = "visible")]
+    pub visible: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub point: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub polyline: Vec<Point>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+fn visible() -> bool {
+    true
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
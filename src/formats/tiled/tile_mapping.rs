@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use crate::map::layers::LayerType;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileMapping {
+    tiles: HashMap<LayerType, u32>,
+}
+
+// Tile ids count from 1 in the order of LayerType::ALL, 0 is Tiled's empty tile.
+impl Default for TileMapping {
+    fn default() -> Self {
+        Self {
+            tiles: LayerType::ALL
+                .iter()
+                .enumerate()
+                .filter(|(_, layer_type)| **layer_type != LayerType::Empty)
+                .map(|(index, layer_type)| (*layer_type, index as u32))
+                .collect(),
+        }
+    }
+}
+
+impl TileMapping {
+    pub fn with_tile(mut self, layer_type: LayerType, tile_id: u32) -> Self {
+        self.tiles.insert(layer_type, tile_id);
+        self
+    }
+
+    pub fn tile_id(&self, layer_type: &LayerType) -> u32 {
+        self.tiles.get(layer_type).copied().unwrap_or_default()
+    }
+
+    pub fn layer_type(&self, tile_id: u32) -> Option<LayerType> {
+        self.tiles
+            .iter()
+            .find(|(_, id)| **id == tile_id)
+            .map(|(layer_type, _)| *layer_type)
+    }
+
+    pub fn tile_count(&self) -> u32 {
+        self.tiles.values().copied().max().unwrap_or_default()
+    }
+}
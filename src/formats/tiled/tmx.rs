@@ -0,0 +1,399 @@
+use std::fmt::Write;
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::generation::GenerationError;
+
+use super::model::{Layer, Object, ObjectGroup, Point, Property, TileLayer, TiledMap, Tileset};
+
+pub(crate) fn write(map: &TiledMap) -> String {
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<map version="{}" orientation="{}" renderorder="{}" width="{}" height="{}" tilewidth="{}" tileheight="{}" infinite="{}" nextlayerid="{}" nextobjectid="{}">"#,
+        map.version,
+        map.orientation,
+        map.renderorder,
+        map.width,
+        map.height,
+        map.tilewidth,
+        map.tileheight,
+        map.infinite as u8,
+        map.nextlayerid,
+        map.nextobjectid
+    )
+    .unwrap();
+
+    if !map.properties.is_empty() {
+        writeln!(xml, " <properties>").unwrap();
+        for property in map.properties.iter() {
+            writeln!(
+                xml,
+                r#"  <property name="{}" type="{}" value="{}"/>"#,
+                escape(&property.name),
+                property.kind,
+                property.value
+            )
+            .unwrap();
+        }
+        writeln!(xml, " </properties>").unwrap();
+    }
+
+    for tileset in map.tilesets.iter() {
+        write!(xml, r#" <tileset firstgid="{}""#, tileset.firstgid).unwrap();
+        for (name, value) in [
+            ("source", tileset.source.clone()),
+            ("name", tileset.name.clone()),
+            ("tilewidth", tileset.tilewidth.map(|it| it.to_string())),
+            ("tileheight", tileset.tileheight.map(|it| it.to_string())),
+            ("tilecount", tileset.tilecount.map(|it| it.to_string())),
+            ("columns", tileset.columns.map(|it| it.to_string())),
+        ] {
+            if let Some(value) = value {
+                write!(xml, r#" {}="{}""#, name, escape(&value)).unwrap();
+            }
+        }
+        writeln!(xml, "/>").unwrap();
+    }
+
+    for layer in map.layers.iter() {
+        match layer {
+            Layer::TileLayer(layer) => {
+                writeln!(
+                    xml,
+                    r#" <layer id="{}" name="{}" width="{}" height="{}"{}>"#,
+                    layer.id,
+                    escape(&layer.name),
+                    layer.width,
+                    layer.height,
+                    if layer.visible { "" } else { r#" visible="0""# }
+                )
+                .unwrap();
+                writeln!(xml, r#"  <data encoding="csv">"#).unwrap();
+                let rows: Vec<String> = layer
+                    .data
+                    .chunks(layer.width.max(1) as usize)
+                    .map(|row| {
+                        row.iter()
+                            .map(|tile| tile.to_string())
+                            .collect::<Vec<String>>()
+                            .join(",")
+                    })
+                    .collect();
+                writeln!(xml, "{}", rows.join(",\n")).unwrap();
+                writeln!(xml, "  </data>").unwrap();
+                writeln!(xml, " </layer>").unwrap();
+            }
+            Layer::ObjectGroup(group) => {
+                writeln!(
+                    xml,
+                    r#" <objectgroup id="{}" name="{}">"#,
+                    group.id,
+                    escape(&group.name)
+                )
+                .unwrap();
+                for object in group.objects.iter() {
+                    write!(
+                        xml,
+                        r#"  <object id="{}" name="{}" type="{}" x="{}" y="{}""#,
+                        object.id,
+                        escape(&object.name),
+                        escape(&object.kind),
+                        object.x,
+                        object.y
+                    )
+                    .unwrap();
+                    if object.width > 0.0 || object.height > 0.0 {
+                        write!(
+                            xml,
+                            r#" width="{}" height="{}""#,
+                            object.width, object.height
+                        )
+                        .unwrap();
+                    }
+
+                    if object.point {
+                        writeln!(xml, ">\n   <point/>\n  </object>").unwrap();
+                    } else if !object.polyline.is_empty() {
+                        let points: Vec<String> = object
+                            .polyline
+                            .iter()
+                            .map(|point| format!("{},{}", point.x, point.y))
+                            .collect();
+                        writeln!(
+                            xml,
+                            ">\n   <polyline points=\"{}\"/>\n  </object>",
+                            points.join(" ")
+                        )
+                        .unwrap();
+                    } else {
+                        writeln!(xml, "/>").unwrap();
+                    }
+                }
+                writeln!(xml, " </objectgroup>").unwrap();
+            }
+            Layer::Unsupported => {}
+        }
+    }
+
+    writeln!(xml, "</map>").unwrap();
+    xml
+}
+
+fn escape(value: &str) -> String {
+    quick_xml::escape::escape(value).to_string()
+}
+
+pub(crate) fn read(content: &str) -> Result<TiledMap, GenerationError> {
+    let mut reader = Reader::from_str(content);
+    let mut map: Option<TiledMap> = None;
+    let mut in_data = false;
+    let mut open_elements: Vec<Vec<u8>> = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|error| GenerationError::map_cannot_be_read(&error.to_string()))?;
+
+        let (element, is_empty) = match &event {
+            Event::Start(element) => (Some(element), false),
+            Event::Empty(element) => (Some(element), true),
+            _ => (None, false),
+        };
+
+        if let Some(element) = element {
+            let attributes = Attributes::of(element)?;
+            // Only properties on the map itself are read, anything nested
+            // deeper belongs to a tileset, layer or object.
+            let is_map_property = open_elements.len() == 2 && open_elements[1] == b"properties";
+            if !is_empty {
+                open_elements.push(element.name().as_ref().to_vec());
+            }
+
+            if element.name().as_ref() == b"map" {
+                map = Some(TiledMap {
+                    kind: "map".to_string(),
+                    version: attributes.text("version"),
+                    orientation: attributes.text("orientation"),
+                    renderorder: attributes.text("renderorder"),
+                    width: attributes.number("width")?,
+                    height: attributes.number("height")?,
+                    tilewidth: attributes.number("tilewidth")?,
+                    tileheight: attributes.number("tileheight")?,
+                    infinite: attributes.text("infinite") == "1",
+                    nextlayerid: attributes.number_or_default("nextlayerid")?,
+                    nextobjectid: attributes.number_or_default("nextobjectid")?,
+                    properties: Vec::new(),
+                    tilesets: Vec::new(),
+                    layers: Vec::new(),
+                });
+                continue;
+            }
+
+            let map = map
+                .as_mut()
+                .ok_or_else(|| GenerationError::map_cannot_be_read("missing <map> element"))?;
+
+            match element.name().as_ref() {
+                b"property" if is_map_property => map.properties.push(Property {
+                    name: attributes.text("name"),
+                    kind: attributes.text("type"),
+                    value: attributes.number("value")?,
+                }),
+                b"tileset" => map.tilesets.push(Tileset {
+                    firstgid: attributes.number("firstgid")?,
+                    source: attributes.optional("source"),
+                    name: attributes.optional("name"),
+                    tilewidth: attributes.optional_number("tilewidth")?,
+                    tileheight: attributes.optional_number("tileheight")?,
+                    tilecount: attributes.optional_number("tilecount")?,
+                    columns: attributes.optional_number("columns")?,
+                }),
+                b"layer" => map.layers.push(Layer::TileLayer(TileLayer {
+                    id: attributes.number_or_default("id")?,
+                    name: attributes.text("name"),
+                    width: attributes.number("width")?,
+                    height: attributes.number("height")?,
+                    x: 0,
+                    y: 0,
+                    opacity: 1.0,
+                    visible: attributes.text("visible") != "0",
+                    data: Vec::new(),
+                })),
+                b"data" => {
+                    let encoding = attributes.text("encoding");
+                    if encoding != "csv" {
+                        return Err(GenerationError::map_cannot_be_read(&format!(
+                            "tile layer data must be csv encoded, not {:?}",
+                            encoding
+                        )));
+                    }
+                    in_data = !is_empty;
+                }
+                b"objectgroup" => map.layers.push(Layer::ObjectGroup(ObjectGroup {
+                    id: attributes.number_or_default("id")?,
+                    name: attributes.text("name"),
+                    x: 0,
+                    y: 0,
+                    opacity: 1.0,
+                    visible: attributes.text("visible") != "0",
+                    draworder: attributes.text("draworder"),
+                    objects: Vec::new(),
+                })),
+                b"object" => {
+                    if let Some(Layer::ObjectGroup(group)) = map.layers.last_mut() {
+                        group.objects.push(Object {
+                            id: attributes.number_or_default("id")?,
+                            name: attributes.text("name"),
+                            kind: attributes
+                                .optional("type")
+                                .unwrap_or_else(|| attributes.text("class")),
+                            x: attributes.number("x")?,
+                            y: attributes.number("y")?,
+                            width: attributes.number_or_default("width")?,
+                            height: attributes.number_or_default("height")?,
+                            rotation: attributes.number_or_default("rotation")?,
+                            visible: attributes.text("visible") != "0",
+                            point: false,
+                            polyline: Vec::new(),
+                        });
+                    }
+                }
+                b"point" => {
+                    if let Some(object) = last_object(map) {
+                        object.point = true;
+                    }
+                }
+                b"polyline" => {
+                    if let Some(object) = last_object(map) {
+                        object.polyline = parse_points(&attributes.text("points"))?;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Text(text) if in_data => {
+                let text = text
+                    .unescape()
+                    .map_err(|error| GenerationError::map_cannot_be_read(&error.to_string()))?;
+                if let Some(Layer::TileLayer(layer)) =
+                    map.as_mut().and_then(|map| map.layers.last_mut())
+                {
+                    for tile in text
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tile| !tile.is_empty())
+                    {
+                        layer.data.push(tile.parse().map_err(|_| {
+                            GenerationError::map_cannot_be_read(&format!(
+                                "{:?} is not a tile id",
+                                tile
+                            ))
+                        })?);
+                    }
+                }
+            }
+            Event::End(element) => {
+                if element.name().as_ref() == b"data" {
+                    in_data = false;
+                }
+                open_elements.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    map.ok_or_else(|| GenerationError::map_cannot_be_read("missing <map> element"))
+}
+
+fn last_object(map: &mut TiledMap) -> Option<&mut Object> {
+    match map.layers.last_mut() {
+        Some(Layer::ObjectGroup(group)) => group.objects.last_mut(),
+        _ => None,
+    }
+}
+
+fn parse_points(points: &str) -> Result<Vec<Point>, GenerationError> {
+    points
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point.split_once(',').unwrap_or((point, ""));
+            match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Ok(Point { x, y }),
+                _ => Err(GenerationError::map_cannot_be_read(&format!(
+                    "{:?} is not a polyline point",
+                    point
+                ))),
+            }
+        })
+        .collect()
+}
+
+struct Attributes(Vec<(String, String)>);
+
+impl Attributes {
+    fn of(element: &BytesStart) -> Result<Self, GenerationError> {
+        let mut attributes = Vec::new();
+        for attribute in element.attributes() {
+            let attribute = attribute
+                .map_err(|error| GenerationError::map_cannot_be_read(&error.to_string()))?;
+            let value = attribute
+                .unescape_value()
+                .map_err(|error| GenerationError::map_cannot_be_read(&error.to_string()))?;
+            attributes.push((
+                String::from_utf8_lossy(attribute.key.as_ref()).to_string(),
+                value.to_string(),
+            ));
+        }
+
+        Ok(Self(attributes))
+    }
+
+    fn optional(&self, name: &str) -> Option<String> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn text(&self, name: &str) -> String {
+        self.optional(name).unwrap_or_default()
+    }
+
+    fn optional_number<T: std::str::FromStr>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>, GenerationError> {
+        self.optional(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    GenerationError::map_cannot_be_read(&format!(
+                        "{}={:?} is not a number",
+                        name, value
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<T, GenerationError> {
+        self.optional_number(name)?.ok_or_else(|| {
+            GenerationError::map_cannot_be_read(&format!("missing {} attribute", name))
+        })
+    }
+
+    fn number_or_default<T: std::str::FromStr + Default>(
+        &self,
+        name: &str,
+    ) -> Result<T, GenerationError> {
+        Ok(self.optional_number(name)?.unwrap_or_default())
+    }
+}
@@ -1,6 +1,14 @@
-use std::num::NonZeroU16;
+use std::{collections::HashSet, num::NonZeroU16};
 
-use super::{assets::RoomTemplates, Generator, ItemGeneration};
+use crate::map::{
+    cells::{Cell, Coordinate},
+    DoorPolicy, Grid, Room,
+};
+
+use super::{
+    assets::RoomTemplates, ConnectivityRepair, ExitConstraints, Generator, ItemGeneration,
+    SeedVersion,
+};
 
 #[derive(Default, Clone)]
 pub struct GeneratorBuilder {
@@ -11,6 +19,16 @@ pub struct GeneratorBuilder {
     target_hidden_items: Option<ItemGeneration>,
     target_items: Option<ItemGeneration>,
     include_outer_wall: Option<bool>,
+    exit_constraints: Option<ExitConstraints>,
+    connectivity_repair: Option<ConnectivityRepair>,
+    door_policy: Option<DoorPolicy>,
+    target_num_locks: Option<usize>,
+    entry_coordinate: Option<Coordinate>,
+    mask: Option<Vec<Cell>>,
+    blocked_coordinates: Option<HashSet<Coordinate>>,
+    pre_placed_rooms: Option<Vec<Room>>,
+    seed_version: Option<SeedVersion>,
+    torch_spacing: Option<NonZeroU16>,
 }
 
 pub fn builder() -> GeneratorBuilder {
@@ -35,7 +53,7 @@ impl GeneratorBuilder {
             .target_number_rooms
             .unwrap_or_else(|| NonZeroU16::new(DEFAULT_TARGET_NUMBER_ROOMS).unwrap());
 
-        Generator::new(
+        let mut generator = Generator::new(
             &seed,
             grid_size,
             target_number_rooms,
@@ -43,7 +61,19 @@ impl GeneratorBuilder {
             self.target_hidden_items.clone(),
             self.room_templates.to_owned().unwrap_or_default(),
             self.include_outer_wall.unwrap_or_default(),
-        )
+        );
+        generator.exit_constraints = self.exit_constraints.clone().unwrap_or_default();
+        generator.connectivity_repair = self.connectivity_repair.unwrap_or_default();
+        generator.door_policy = self.door_policy.clone().unwrap_or_default();
+        generator.target_num_locks = self.target_num_locks.unwrap_or_default();
+        generator.entry_coordinate = self.entry_coordinate;
+        generator.mask = self.mask.clone().unwrap_or_default();
+        generator.blocked_coordinates = self.blocked_coordinates.clone().unwrap_or_default();
+        generator.pre_placed_rooms = self.pre_placed_rooms.clone().unwrap_or_default();
+        generator.seed_version = self.seed_version.unwrap_or_default();
+        generator.torch_spacing = self.torch_spacing;
+
+        generator
     }
 
     pub fn seed(&mut self, seed: &str) -> &mut GeneratorBuilder {
@@ -93,4 +123,83 @@ impl GeneratorBuilder {
 
         self
     }
+
+    pub fn exit_constraints(&mut self, exit_constraints: ExitConstraints) -> &mut GeneratorBuilder {
+        self.exit_constraints = Some(exit_constraints);
+
+        self
+    }
+
+    pub fn connectivity_repair(
+        &mut self,
+        connectivity_repair: ConnectivityRepair,
+    ) -> &mut GeneratorBuilder {
+        self.connectivity_repair = Some(connectivity_repair);
+
+        self
+    }
+
+    pub fn door_policy(&mut self, door_policy: DoorPolicy) -> &mut GeneratorBuilder {
+        self.door_policy = Some(door_policy);
+
+        self
+    }
+
+    // Locks only go on plain doors along the entrance to exit path whose cell
+    // cuts the exit off on its own, so maps with loops or doorless gaps can
+    // end up with fewer locks than asked for.
+    pub fn target_num_locks(&mut self, target_num_locks: usize) -> &mut GeneratorBuilder {
+        self.target_num_locks = Some(target_num_locks);
+
+        self
+    }
+
+    pub fn entry_coordinate(&mut self, entry_coordinate: Coordinate) -> &mut GeneratorBuilder {
+        self.entry_coordinate = Some(entry_coordinate);
+
+        self
+    }
+
+    pub fn mask(&mut self, mask: &Grid) -> &mut GeneratorBuilder {
+        self.mask = Some(
+            mask.cells()
+                .into_iter()
+                .filter(|cell| !cell.is_empty())
+                .cloned()
+                .collect(),
+        );
+
+        self
+    }
+
+    pub fn blocked_coordinates(
+        &mut self,
+        blocked_coordinates: HashSet<Coordinate>,
+    ) -> &mut GeneratorBuilder {
+        self.blocked_coordinates = Some(blocked_coordinates);
+
+        self
+    }
+
+    pub fn pre_placed_rooms(&mut self, pre_placed_rooms: Vec<Room>) -> &mut GeneratorBuilder {
+        self.pre_placed_rooms = Some(pre_placed_rooms);
+
+        self
+    }
+
+    pub fn seed_version(&mut self, seed_version: SeedVersion) -> &mut GeneratorBuilder {
+        self.seed_version = Some(seed_version);
+
+        self
+    }
+
+    pub(crate) fn configured_seed_version(&self) -> SeedVersion {
+        self.seed_version.unwrap_or_default()
+    }
+
+    pub fn torch_spacing(&mut self, torch_spacing: NonZeroU16) -> &mut GeneratorBuilder {
+        self.torch_spacing = Some(torch_spacing);
+
+        self
+    }
 }
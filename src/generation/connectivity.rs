@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use pathfinding::prelude::dijkstra;
+use serde::{Deserialize, Serialize};
+
+use crate::map::{cells::Coordinate, Grid};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityRepair {
+    #[default]
+    Ignore,
+    Carve,
+    Fill,
+}
+
+const BLOCKED_STEP_COST: u32 = 4;
+
+impl ConnectivityRepair {
+    pub fn apply(&self, grid: &mut Grid) {
+        self.apply_around(grid, None);
+    }
+
+    // Keeps the region holding `anchor` (e.g. a forced entry) instead of the
+    // largest one.
+    pub fn apply_around(&self, grid: &mut Grid, anchor: Option<&Coordinate>) {
+        let mut regions = grid.regions();
+        if let Some(index) =
+            anchor.and_then(|anchor| regions.iter().position(|region| region.contains(anchor)))
+        {
+            let anchored = regions.remove(index);
+            regions.insert(0, anchored);
+        }
+        let (main_region, isolated_regions) = match regions.split_first() {
+            Some(it) => it,
+            None => return,
+        };
+
+        match self {
+            ConnectivityRepair::Ignore => {}
+            ConnectivityRepair::Fill => {
+                for coordinate in isolated_regions
+                    .iter()
+                    .flat_map(|region| region.coordinates())
+                {
+                    grid.fill_with_rock(coordinate);
+                }
+            }
+            ConnectivityRepair::Carve => {
+                let mut connected: HashSet<Coordinate> =
+                    main_region.coordinates().iter().copied().collect();
+
+                for region in isolated_regions {
+                    if region
+                        .coordinates()
+                        .iter()
+                        .any(|coordinate| connected.contains(coordinate))
+                    {
+                        continue;
+                    }
+
+                    let start = region.coordinates()[0];
+                    let tunnel = dijkstra(
+                        &start,
+                        |coordinate| {
+                            coordinate
+                                .neighbours()
+                                .into_iter()
+                                .filter(|neighbour| {
+                                    grid.is_in_bounds(neighbour) && !grid.is_blocked(neighbour)
+                                })
+                                .filter_map(|neighbour| grid.cell(&neighbour))
+                                .map(|cell| {
+                                    let cost = if cell.is_walkable() {
+                                        1
+                                    } else {
+                                        BLOCKED_STEP_COST
+                                    };
+                                    (*cell.coordinate(), cost)
+                                })
+                                .collect::<Vec<(Coordinate, u32)>>()
+                        },
+                        |coordinate| connected.contains(coordinate),
+                    );
+
+                    if let Some((tunnel, _)) = tunnel {
+                        for coordinate in tunnel.iter() {
+                            grid.carve(coordinate);
+                        }
+                        connected.extend(tunnel);
+                    }
+
+                    connected.extend(region.coordinates().iter().copied());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generation::{builder, ConnectivityRepair},
+        map::{
+            cells::Coordinate,
+            layers::{LayerType, StructureType},
+            Grid,
+        },
+    };
+
+    fn walled_grid() -> Grid {
+        let mut grid = Grid::build(5, "connectivity".to_string());
+        grid.fill_empty_cells();
+        for y in 0..5 {
+            grid.add_layer(
+                &Coordinate::from((2, y)),
+                LayerType::Structure(StructureType::Wall),
+            );
+        }
+        grid.add_layer(
+            &Coordinate::from((4, 4)),
+            LayerType::Structure(StructureType::Wall),
+        );
+        grid
+    }
+
+    #[test]
+    fn regions_are_labelled_largest_first() {
+        let grid = walled_grid();
+        let regions = grid.regions();
+
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].size(), 10);
+        assert_eq!(regions[1].size(), 9);
+        assert!(regions[1].contains(&Coordinate::from((3, 0))));
+    }
+
+    #[test]
+    fn carve_joins_isolated_regions() {
+        let mut grid = walled_grid();
+        ConnectivityRepair::Carve.apply(&mut grid);
+
+        assert_eq!(grid.regions().len(), 1);
+        assert!(grid
+            .cell(&Coordinate::from((2, 0)))
+            .unwrap()
+            .contains_door());
+    }
+
+    #[test]
+    fn carve_goes_around_blocked_cells() {
+        let mut grid = walled_grid();
+        for y in 0..4 {
+            grid.block(&Coordinate::from((2, y)));
+        }
+        ConnectivityRepair::Carve.apply(&mut grid);
+
+        assert_eq!(grid.regions().len(), 1);
+        assert!(grid
+            .cell(&Coordinate::from((2, 4)))
+            .unwrap()
+            .contains_door());
+        assert!((0..4).all(|y| !grid.cell(&Coordinate::from((2, y))).unwrap().is_walkable()));
+    }
+
+    #[test]
+    fn fill_turns_isolated_regions_to_rock() {
+        let mut grid = walled_grid();
+        ConnectivityRepair::Fill.apply(&mut grid);
+
+        assert_eq!(grid.regions().len(), 1);
+        assert_eq!(
+            grid.cell(&Coordinate::from((3, 0)))
+                .unwrap()
+                .visible_layer(),
+            LayerType::Structure(StructureType::Boulder)
+        );
+    }
+
+    #[test]
+    fn fill_keeps_the_anchored_region() {
+        let mut grid = walled_grid();
+        let anchor = Coordinate::from((3, 0));
+        ConnectivityRepair::Fill.apply_around(&mut grid, Some(&anchor));
+
+        let regions = grid.regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].size(), 9);
+        assert!(regions[0].contains(&anchor));
+    }
+
+    #[test]
+    fn generated_maps_are_connected() {
+        for repair in [ConnectivityRepair::Carve, ConnectivityRepair::Fill] {
+            let mut generator = builder()
+                .seed("sealed pockets")
+                .connectivity_repair(repair)
+                .build();
+            let map = generator.generate_top_down_map().unwrap();
+
+            assert_eq!(map.grid().regions().len(), 1);
+            assert!(map.path().is_some());
+        }
+    }
+}
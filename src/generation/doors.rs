@@ -0,0 +1,219 @@
+use rand::Rng;
+
+use crate::map::{
+    cells::Coordinate,
+    layers::{LayerType, StructureType},
+    DoorPolicy, Room,
+};
+
+pub(crate) fn place_doors<R: Rng>(
+    room: &mut Room,
+    policy: &DoorPolicy,
+    neighbouring_rooms: &[Room],
+    rng: &mut R,
+) -> Option<Vec<Coordinate>> {
+    let mut candidates: Vec<Coordinate> = room
+        .possible_door_cells()
+        .iter()
+        .map(|cell| *cell.coordinate())
+        .collect();
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let door_count = if policy.door_count.start() >= policy.door_count.end() {
+        *policy.door_count.start()
+    } else {
+        rng.gen_range(policy.door_count.clone())
+    };
+
+    let mut doors: Vec<Coordinate> = Vec::new();
+    let mut used_sides: Vec<usize> = Vec::new();
+
+    if policy.face_nearest_room && door_count > 0 {
+        let center = room.center();
+        let nearest_room_center = neighbouring_rooms
+            .iter()
+            .map(|other| other.center())
+            .min_by_key(|other_center| other_center.distance(&center));
+
+        if let Some(target) = nearest_room_center {
+            let index = (0..candidates.len())
+                .min_by_key(|index| candidates[*index].distance(&target))
+                .unwrap();
+            take_door(room, &mut candidates, index, &mut doors, &mut used_sides);
+        }
+    }
+
+    while doors.len() < door_count as usize && !candidates.is_empty() {
+        let mut pool: Vec<usize> = (0..candidates.len()).collect();
+
+        if policy.prefer_different_sides {
+            let unused: Vec<usize> = pool
+                .iter()
+                .copied()
+                .filter(|index| {
+                    exterior_side(room, &candidates[*index])
+                        .is_some_and(|side| !used_sides.contains(&side))
+                })
+                .collect();
+            if !unused.is_empty() {
+                pool = unused;
+            }
+        }
+
+        let index = pool[rng.gen_range(0..pool.len())];
+        take_door(room, &mut candidates, index, &mut doors, &mut used_sides);
+    }
+
+    for door in doors.iter() {
+        let door_type = roll_door_type(policy, rng);
+        room.replace_cell_contents(door.x(), door.y(), LayerType::Structure(door_type));
+    }
+
+    Some(doors)
+}
+
+fn take_door(
+    room: &Room,
+    candidates: &mut Vec<Coordinate>,
+    index: usize,
+    doors: &mut Vec<Coordinate>,
+    used_sides: &mut Vec<usize>,
+) {
+    let door = candidates.remove(index);
+    if let Some(side) = exterior_side(room, &door) {
+        used_sides.push(side);
+    }
+    // Keep doors from ending up right beside each other.
+    candidates.retain(|candidate| candidate.distance(&door) > 1);
+    doors.push(door);
+}
+
+fn exterior_side(room: &Room, coordinate: &Coordinate) -> Option<usize> {
+    coordinate
+        .neighbours()
+        .iter()
+        .position(|neighbour| room.cell_at(neighbour.x(), neighbour.y()).is_none())
+}
+
+fn roll_door_type<R: Rng>(policy: &DoorPolicy, rng: &mut R) -> StructureType {
+    let open = policy.open_chance as u16;
+    let locked = open + policy.locked_chance as u16;
+    let secret = locked + policy.secret_chance as u16;
+
+    if secret == 0 {
+        return StructureType::Door;
+    }
+
+    let roll: u16 = rng.gen_range(1..=100);
+    if roll <= open {
+        StructureType::OpenDoor
+    } else if roll <= locked {
+        StructureType::LockedDoor
+    } else if roll <= secret {
+        StructureType::SecretDoor
+    } else {
+        StructureType::Door
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_pcg::Pcg64;
+    use rand_seeder::Seeder;
+
+    use crate::map::{
+        layers::{LayerType, StructureType},
+        DoorPolicy, Room,
+    };
+
+    use super::place_doors;
+
+    const SQUARE: &str = "
+    ||||||
+    |====|
+    |====|
+    |====|
+    |====|
+    ||||||
+    ";
+
+    fn door_layers(room: &Room) -> Vec<LayerType> {
+        room.cells()
+            .iter()
+            .filter(|cell| cell.contains_door())
+            .map(|cell| cell.visible_layer())
+            .collect()
+    }
+
+    #[test]
+    fn places_doors_within_count_range() {
+        let mut rng: Pcg64 = Seeder::from("doors").make_rng();
+        let policy = DoorPolicy {
+            door_count: 2..=3,
+            prefer_different_sides: true,
+            ..Default::default()
+        };
+
+        for _ in 0..10 {
+            let mut room = Room::from(SQUARE);
+            let doors = place_doors(&mut room, &policy, &[], &mut rng).unwrap();
+
+            assert!((2..=3).contains(&doors.len()));
+            assert_eq!(door_layers(&room).len(), doors.len());
+        }
+    }
+
+    #[test]
+    fn prefers_different_sides() {
+        let mut rng: Pcg64 = Seeder::from("sides").make_rng();
+        let policy = DoorPolicy {
+            door_count: 4..=4,
+            prefer_different_sides: true,
+            ..Default::default()
+        };
+
+        let mut room = Room::from(SQUARE);
+        let doors = place_doors(&mut room, &policy, &[], &mut rng).unwrap();
+
+        assert_eq!(doors.iter().filter(|door| door.x() == 0).count(), 1);
+        assert_eq!(doors.iter().filter(|door| door.x() == 5).count(), 1);
+        assert_eq!(doors.iter().filter(|door| door.y() == 0).count(), 1);
+        assert_eq!(doors.iter().filter(|door| door.y() == 5).count(), 1);
+    }
+
+    #[test]
+    fn faces_nearest_room() {
+        let neighbours = vec![Room::from(SQUARE).translate(20, 0)];
+        let mut rng: Pcg64 = Seeder::from("facing").make_rng();
+        let policy = DoorPolicy {
+            face_nearest_room: true,
+            ..Default::default()
+        };
+
+        let mut room = Room::from(SQUARE);
+        let doors = place_doors(&mut room, &policy, &neighbours, &mut rng).unwrap();
+
+        assert_eq!(doors.len(), 1);
+        assert_eq!(doors[0].x(), 5);
+    }
+
+    #[test]
+    fn rolls_door_variants() {
+        let mut rng: Pcg64 = Seeder::from("variants").make_rng();
+        let policy = DoorPolicy {
+            locked_chance: 100,
+            ..Default::default()
+        };
+
+        let mut room = Room::from(SQUARE);
+        place_doors(&mut room, &policy, &[], &mut rng).unwrap();
+
+        assert_eq!(
+            door_layers(&room),
+            vec![LayerType::Structure(StructureType::LockedDoor)]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::map::TopDownMap;
+
+use super::{GenerationError, GeneratorBuilder};
+
+type LevelConfiguration = Box<dyn Fn(usize, &mut GeneratorBuilder)>;
+
+pub struct Dungeon {
+    seed: String,
+    num_levels: usize,
+    base: GeneratorBuilder,
+    configure_level: Option<LevelConfiguration>,
+    levels: Vec<TopDownMap>,
+}
+
+impl Dungeon {
+    pub fn new(seed: &str, num_levels: usize, base: GeneratorBuilder) -> Self {
+        Self {
+            seed: seed.to_string(),
+            num_levels,
+            base,
+            configure_level: None,
+            levels: Vec::new(),
+        }
+    }
+
+    pub fn configure_levels(
+        mut self,
+        configure_level: impl Fn(usize, &mut GeneratorBuilder) + 'static,
+    ) -> Self {
+        self.configure_level = Some(Box::new(configure_level));
+        self
+    }
+
+    pub fn seed(&self) -> &str {
+        &self.seed
+    }
+
+    pub fn num_levels(&self) -> usize {
+        self.num_levels
+    }
+
+    // Levels follow the base builder's seed version.
+    pub fn level_seed(&self, depth: usize) -> String {
+        self.base
+            .configured_seed_version()
+            .level_seed(&self.seed, depth)
+    }
+
+    pub fn generated_levels(&self) -> &Vec<TopDownMap> {
+        &self.levels
+    }
+
+    pub fn level(&mut self, depth: usize) -> Result<&TopDownMap, GenerationError> {
+        self.generate_to(depth)?;
+        Ok(&self.levels[depth])
+    }
+
+    pub fn level_mut(&mut self, depth: usize) -> Result<&mut TopDownMap, GenerationError> {
+        self.generate_to(depth)?;
+        Ok(&mut self.levels[depth])
+    }
+
+    fn generate_to(&mut self, depth: usize) -> Result<(), GenerationError> {
+        if depth >= self.num_levels {
+            return Err(GenerationError::level_out_of_range());
+        }
+
+        while self.levels.len() <= depth {
+            let level_depth = self.levels.len();
+            let mut builder = self.base.clone();
+            builder.seed(&self.level_seed(level_depth));
+
+            if let Some(configure_level) = &self.configure_level {
+                configure_level(level_depth, &mut builder);
+            }
+
+            // The stairs down from the previous floor land on this floor's entrance.
+            if let Some(previous) = self.levels.last() {
+                builder.entry_coordinate(*previous.exit());
+            }
+
+            let level = builder.build().generate_top_down_map()?;
+            self.levels.push(level);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::{builder, ItemChance, ItemGeneration, SeedVersion},
+        map::layers::{ItemRarity, LayerType},
+    };
+
+    use super::Dungeon;
+
+    fn dungeon() -> Dungeon {
+        let mut base = builder();
+        base.grid_size(NonZeroU16::new(40).unwrap());
+
+        Dungeon::new("deep", 3, base).configure_levels(|depth, builder| {
+            builder.target_items(ItemGeneration {
+                target_num_items: 5 * (depth + 1),
+                item_ranges: vec![ItemChance {
+                    layer_type: LayerType::Item(ItemRarity::Common),
+                    chance: 0..100,
+                }],
+            });
+        })
+    }
+
+    #[test]
+    fn level_seeds_follow_the_seed_version() {
+        let mut derived = builder();
+        derived.seed_version(SeedVersion::V2);
+        let derived = Dungeon::new("deep", 3, derived);
+
+        assert_eq!(dungeon().level_seed(1), "deep:level:1");
+        assert_ne!(derived.level_seed(1), dungeon().level_seed(1));
+        assert_ne!(derived.level_seed(1), derived.level_seed(2));
+        assert_eq!(derived.level_seed(1), SeedVersion::V2.level_seed("deep", 1));
+    }
+
+    #[test]
+    fn smaller_levels_must_still_fit_the_stairs() {
+        let mut dungeon = dungeon().configure_levels(|depth, builder| {
+            if depth > 0 {
+                builder.grid_size(NonZeroU16::new(12).unwrap());
+            }
+        });
+        let exit = *dungeon.level(0).unwrap().exit();
+        assert!(exit.x() >= 12 || exit.y() >= 12);
+
+        assert_eq!(dungeon.level(1).unwrap_err().message, "entry_out_of_bounds");
+        assert_eq!(dungeon.generated_levels().len(), 1);
+    }
+
+    #[test]
+    fn levels_are_generated_lazily() {
+        let mut dungeon = dungeon();
+
+        dungeon.level(0).unwrap();
+        assert_eq!(dungeon.generated_levels().len(), 1);
+
+        dungeon.level(2).unwrap();
+        assert_eq!(dungeon.generated_levels().len(), 3);
+
+        assert!(dungeon.level(3).is_err());
+    }
+
+    #[test]
+    fn stairs_line_up_between_levels() {
+        let mut dungeon = dungeon();
+        dungeon.level(2).unwrap();
+        let levels = dungeon.generated_levels();
+
+        for pair in levels.windows(2) {
+            assert_eq!(pair[0].exit(), pair[1].entry());
+            assert_eq!(
+                pair[1]
+                    .grid()
+                    .cell(pair[1].entry())
+                    .unwrap()
+                    .visible_layer(),
+                LayerType::Entrance
+            );
+        }
+    }
+
+    #[test]
+    fn same_seed_builds_same_levels() {
+        let mut first = dungeon();
+        let mut second = dungeon();
+
+        assert_eq!(
+            first.level(1).unwrap().grid().top_layer_display(),
+            second.level(1).unwrap().grid().top_layer_display()
+        );
+    }
+}
@@ -15,4 +15,82 @@ impl GenerationError {
             message: "room_templates_cannot_be_loaded".to_string(),
         }
     }
+
+    pub fn exit_constraints_unsatisfied() -> Self {
+        Self {
+            message: "exit_constraints_unsatisfied".to_string(),
+        }
+    }
+
+    pub fn entry_out_of_bounds() -> Self {
+        Self {
+            message: "entry_out_of_bounds".to_string(),
+        }
+    }
+
+    pub fn entry_blocked() -> Self {
+        Self {
+            message: "entry_blocked".to_string(),
+        }
+    }
+
+    pub fn no_spawnable_cells() -> Self {
+        Self {
+            message: "no_spawnable_cells".to_string(),
+        }
+    }
+
+    pub fn level_out_of_range() -> Self {
+        Self {
+            message: "level_out_of_range".to_string(),
+        }
+    }
+
+    pub fn invalid_room_template(reason: &str) -> Self {
+        Self {
+            message: format!("invalid_room_template: {}", reason),
+        }
+    }
+
+    pub fn room_templates_too_large() -> Self {
+        Self {
+            message: "room_templates_too_large".to_string(),
+        }
+    }
+
+    pub fn pre_placed_room_blocked() -> Self {
+        Self {
+            message: "pre_placed_room_blocked".to_string(),
+        }
+    }
+
+    pub fn invalid_config(reason: &str) -> Self {
+        Self {
+            message: format!("invalid_config: {}", reason),
+        }
+    }
+
+    pub fn config_cannot_be_read(reason: &str) -> Self {
+        Self {
+            message: format!("config_cannot_be_read: {}", reason),
+        }
+    }
+
+    pub fn config_cannot_be_parsed(reason: &str) -> Self {
+        Self {
+            message: format!("config_cannot_be_parsed: {}", reason),
+        }
+    }
+
+    pub fn map_cannot_be_written(reason: &str) -> Self {
+        Self {
+            message: format!("map_cannot_be_written: {}", reason),
+        }
+    }
+
+    pub fn map_cannot_be_read(reason: &str) -> Self {
+        Self {
+            message: format!("map_cannot_be_read: {}", reason),
+        }
+    }
 }
@@ -0,0 +1,155 @@
+use std::num::NonZeroU16;
+
+use serde::{Deserialize, Serialize};
+
+use crate::map::{cells::Coordinate, layers::FloorType, Grid};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExitConstraints {
+    pub require_reachable: bool,
+    pub min_path_length: Option<usize>,
+    pub max_path_length: Option<usize>,
+    pub separate_rooms: bool,
+    pub entry_floor: Option<FloorType>,
+    pub exit_floor: Option<FloorType>,
+    pub max_attempts: NonZeroU16,
+}
+
+const DEFAULT_MAX_ATTEMPTS: u16 = 100;
+
+impl Default for ExitConstraints {
+    fn default() -> Self {
+        Self {
+            require_reachable: false,
+            min_path_length: None,
+            max_path_length: None,
+            separate_rooms: false,
+            entry_floor: None,
+            exit_floor: None,
+            max_attempts: NonZeroU16::new(DEFAULT_MAX_ATTEMPTS).unwrap(),
+        }
+    }
+}
+
+impl ExitConstraints {
+    pub fn is_satisfied_by(
+        &self,
+        grid: &Grid,
+        entry: &Coordinate,
+        exit: &Coordinate,
+        path: Option<&Vec<Coordinate>>,
+    ) -> bool {
+        let needs_path = self.require_reachable
+            || self.min_path_length.is_some()
+            || self.max_path_length.is_some();
+
+        if needs_path {
+            let path_length = match path {
+                Some(it) => it.len().saturating_sub(1),
+                None => return false,
+            };
+
+            if self.min_path_length.is_some_and(|min| path_length < min)
+                || self.max_path_length.is_some_and(|max| path_length > max)
+            {
+                return false;
+            }
+        }
+
+        if self.separate_rooms {
+            if let (Some(entry_room), Some(exit_room)) =
+                (grid.room_index_at(entry), grid.room_index_at(exit))
+            {
+                if entry_room == exit_room {
+                    return false;
+                }
+            }
+        }
+
+        Self::floor_matches(grid, entry, self.entry_floor)
+            && Self::floor_matches(grid, exit, self.exit_floor)
+    }
+
+    fn floor_matches(grid: &Grid, coordinate: &Coordinate, floor: Option<FloorType>) -> bool {
+        match floor {
+            Some(floor_type) => grid
+                .cell(coordinate)
+                .and_then(|cell| cell.floor_type())
+                .is_some_and(|it| it == floor_type),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::{builder, ExitConstraints},
+        map::layers::FloorType,
+    };
+
+    #[test]
+    fn exit_is_reachable_within_range() {
+        let mut generator = builder()
+            .seed("exit constraints")
+            .grid_size(NonZeroU16::new(50).unwrap())
+            .exit_constraints(ExitConstraints {
+                require_reachable: true,
+                min_path_length: Some(20),
+                max_path_length: Some(60),
+                separate_rooms: true,
+                ..Default::default()
+            })
+            .build();
+
+        let map = generator.generate_top_down_map().unwrap();
+        let path_length = map.path_length().unwrap();
+
+        assert!((20..=60).contains(&path_length));
+        assert_eq!(map.path().unwrap().first(), Some(map.entry()));
+        assert_eq!(map.path().unwrap().last(), Some(map.exit()));
+    }
+
+    #[test]
+    fn entry_and_exit_floors_are_respected() {
+        let mut generator = builder()
+            .seed("indoor outdoor")
+            .exit_constraints(ExitConstraints {
+                entry_floor: Some(FloorType::Outdoor),
+                exit_floor: Some(FloorType::Indoor),
+                ..Default::default()
+            })
+            .build();
+
+        let map = generator.generate_top_down_map().unwrap();
+
+        assert_eq!(
+            map.grid().cell(map.entry()).unwrap().floor_type(),
+            Some(FloorType::Outdoor)
+        );
+        assert_eq!(
+            map.grid().cell(map.exit()).unwrap().floor_type(),
+            Some(FloorType::Indoor)
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_constraints_fail() {
+        let mut generator = builder()
+            .seed("too far")
+            .grid_size(NonZeroU16::new(20).unwrap())
+            .exit_constraints(ExitConstraints {
+                min_path_length: Some(1000),
+                max_attempts: NonZeroU16::new(5).unwrap(),
+                ..Default::default()
+            })
+            .build();
+
+        let error = generator.generate_top_down_map().unwrap_err();
+
+        assert_eq!(error.message, "exit_constraints_unsatisfied");
+    }
+}
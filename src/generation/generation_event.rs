@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::map::{cells::Coordinate, layers::LayerType, LockAndKey};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationPhase {
+    Rooms,
+    Fill,
+    Connectivity,
+    HiddenItems,
+    Items,
+    EntryAndExit,
+    Path,
+    Locks,
+    Torches,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RoomRejection {
+    Blocked,
+    NoDoorCells,
+    Overlap,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerationEvent {
+    PhaseStarted(GenerationPhase),
+    RoomAttempted {
+        template_index: usize,
+        coordinate: Coordinate,
+    },
+    RoomRejected {
+        template_index: usize,
+        coordinate: Coordinate,
+        reason: RoomRejection,
+    },
+    RoomPlaced {
+        template_index: Option<usize>,
+        coordinate: Coordinate,
+    },
+    DoorPlaced {
+        coordinate: Coordinate,
+        layer_type: LayerType,
+    },
+    TablePlaced(Coordinate),
+    ItemBuried {
+        coordinate: Coordinate,
+        layer_type: LayerType,
+    },
+    ItemPlaced {
+        coordinate: Coordinate,
+        layer_type: LayerType,
+    },
+    ItemRollMissed {
+        hidden: bool,
+        roll: usize,
+    },
+    ExitRejected {
+        entry: Coordinate,
+        exit: Coordinate,
+    },
+    EntrancePlaced(Coordinate),
+    ExitPlaced(Coordinate),
+    PathComputed(Option<Vec<Coordinate>>),
+    LockPlaced(LockAndKey),
+    TorchPlaced(Coordinate),
+}
@@ -0,0 +1,268 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::map::{layers::LayerType, TopDownMap};
+
+use super::{GenerationEvent, GenerationPhase, ItemGeneration, RoomRejection};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TemplateStats {
+    pub attempts: usize,
+    pub placed: usize,
+    pub rejected_overlap: usize,
+    pub rejected_blocked: usize,
+    pub rejected_no_door_cells: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LayerCount {
+    pub layer_type: LayerType,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ItemStats {
+    pub requested: usize,
+    pub placed: usize,
+    pub missed_rolls: usize,
+    pub placed_by_type: Vec<LayerCount>,
+}
+
+impl ItemStats {
+    fn requested(item_generation: Option<&ItemGeneration>) -> Self {
+        Self {
+            requested: item_generation
+                .map(|it| it.target_num_items)
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    fn record_placed(&mut self, layer_type: LayerType) {
+        self.placed += 1;
+        match self
+            .placed_by_type
+            .iter_mut()
+            .find(|layer_count| layer_count.layer_type == layer_type)
+        {
+            Some(layer_count) => layer_count.count += 1,
+            None => self.placed_by_type.push(LayerCount {
+                layer_type,
+                count: 1,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PhaseTiming {
+    pub phase: GenerationPhase,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GenerationReport {
+    pub seed: String,
+    pub templates: Vec<TemplateStats>,
+    pub pre_placed_rooms: usize,
+    pub room_count: usize,
+    pub rejected_overlap: usize,
+    pub hidden_items: ItemStats,
+    pub items: ItemStats,
+    pub exit_attempts: usize,
+    pub path_length: Option<usize>,
+    pub walkable_percentage: f32,
+    pub region_count: usize,
+    pub locks_placed: usize,
+    pub torches_placed: usize,
+    pub phase_timings: Vec<PhaseTiming>,
+    pub total_duration: Duration,
+    #[serde(skip)]
+    started: Option<Instant>,
+    #[serde(skip)]
+    current_phase: Option<(GenerationPhase, Instant)>,
+}
+
+impl GenerationReport {
+    pub(crate) fn new(
+        seed: &str,
+        template_count: usize,
+        target_hidden_items: Option<&ItemGeneration>,
+        target_items: Option<&ItemGeneration>,
+    ) -> Self {
+        Self {
+            seed: seed.to_string(),
+            templates: vec![TemplateStats::default(); template_count],
+            pre_placed_rooms: 0,
+            room_count: 0,
+            rejected_overlap: 0,
+            hidden_items: ItemStats::requested(target_hidden_items),
+            items: ItemStats::requested(target_items),
+            exit_attempts: 0,
+            path_length: None,
+            walkable_percentage: 0.0,
+            region_count: 0,
+            locks_placed: 0,
+            torches_placed: 0,
+            phase_timings: Vec::new(),
+            total_duration: Duration::ZERO,
+            started: Some(Instant::now()),
+            current_phase: None,
+        }
+    }
+
+    pub(crate) fn record(&mut self, event: &GenerationEvent) {
+        match event {
+            GenerationEvent::PhaseStarted(phase) => {
+                self.finish_phase();
+                self.current_phase = Some((*phase, Instant::now()));
+            }
+            GenerationEvent::RoomAttempted { template_index, .. } => {
+                self.templates[*template_index].attempts += 1;
+            }
+            GenerationEvent::RoomRejected {
+                template_index,
+                reason,
+                ..
+            } => {
+                let stats = &mut self.templates[*template_index];
+                match reason {
+                    RoomRejection::Blocked => stats.rejected_blocked += 1,
+                    RoomRejection::NoDoorCells => stats.rejected_no_door_cells += 1,
+                    RoomRejection::Overlap => {
+                        stats.rejected_overlap += 1;
+                        self.rejected_overlap += 1;
+                    }
+                }
+            }
+            GenerationEvent::RoomPlaced { template_index, .. } => match template_index {
+                Some(index) => self.templates[*index].placed += 1,
+                None => self.pre_placed_rooms += 1,
+            },
+            GenerationEvent::ItemBuried { layer_type, .. } => {
+                self.hidden_items.record_placed(*layer_type)
+            }
+            GenerationEvent::ItemPlaced { layer_type, .. } => self.items.record_placed(*layer_type),
+            GenerationEvent::ItemRollMissed { hidden, .. } => {
+                if *hidden {
+                    self.hidden_items.missed_rolls += 1;
+                } else {
+                    self.items.missed_rolls += 1;
+                }
+            }
+            GenerationEvent::ExitRejected { .. } => self.exit_attempts += 1,
+            GenerationEvent::ExitPlaced(_) => self.exit_attempts += 1,
+            GenerationEvent::LockPlaced(_) => self.locks_placed += 1,
+            GenerationEvent::TorchPlaced(_) => self.torches_placed += 1,
+            _ => {}
+        }
+    }
+
+    pub(crate) fn finish(&mut self, map: &TopDownMap) {
+        self.finish_phase();
+
+        let grid = map.grid();
+        let in_bounds: Vec<bool> = grid
+            .cells()
+            .iter()
+            .filter(|cell| grid.is_in_bounds(cell.coordinate()))
+            .map(|cell| cell.is_walkable())
+            .collect();
+        let walkable = in_bounds.iter().filter(|walkable| **walkable).count();
+
+        self.room_count = map.room_count();
+        self.path_length = map.path_length();
+        self.walkable_percentage = if in_bounds.is_empty() {
+            0.0
+        } else {
+            walkable as f32 * 100.0 / in_bounds.len() as f32
+        };
+        self.region_count = grid.regions().len();
+        self.total_duration = self
+            .started
+            .take()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+    }
+
+    fn finish_phase(&mut self) {
+        if let Some((phase, started)) = self.current_phase.take() {
+            self.phase_timings.push(PhaseTiming {
+                phase,
+                duration: started.elapsed(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generation::{builder, GenerationPhase, ItemChance, ItemGeneration},
+        map::layers::{ItemRarity, LayerType},
+    };
+
+    #[test]
+    fn report_matches_generated_map() {
+        let mut generator = builder()
+            .seed("balancing")
+            .target_items(ItemGeneration {
+                target_num_items: 40,
+                item_ranges: vec![
+                    ItemChance {
+                        layer_type: LayerType::Item(ItemRarity::Common),
+                        chance: 0..50,
+                    },
+                    ItemChance {
+                        layer_type: LayerType::Item(ItemRarity::Rare),
+                        chance: 60..100,
+                    },
+                ],
+            })
+            .build();
+
+        let (map, report) = generator.generate_with_report().unwrap();
+
+        assert_eq!(
+            report
+                .templates
+                .iter()
+                .map(|stats| stats.attempts)
+                .sum::<usize>(),
+            25
+        );
+        assert_eq!(
+            report
+                .templates
+                .iter()
+                .map(|stats| stats.placed)
+                .sum::<usize>(),
+            map.room_count()
+        );
+        assert_eq!(report.room_count, map.room_count());
+        assert_eq!(report.items.requested, 40);
+        assert!(report.items.missed_rolls > 0);
+        assert_eq!(report.items.placed + report.items.missed_rolls, 40);
+        assert_eq!(
+            report
+                .items
+                .placed_by_type
+                .iter()
+                .map(|layer_count| layer_count.count)
+                .sum::<usize>(),
+            report.items.placed
+        );
+        assert_eq!(report.path_length, map.path_length());
+        assert_eq!(report.region_count, map.grid().regions().len());
+        assert!(report.walkable_percentage > 0.0 && report.walkable_percentage <= 100.0);
+        assert_eq!(
+            report.phase_timings.first().map(|timing| timing.phase),
+            Some(GenerationPhase::Rooms)
+        );
+        assert_eq!(
+            report.phase_timings.last().map(|timing| timing.phase),
+            Some(GenerationPhase::Locks)
+        );
+    }
+}
@@ -1,19 +1,22 @@
-use std::num::NonZeroU16;
+use std::{collections::HashSet, num::NonZeroU16};
 
 use pathfinding::prelude::astar;
 use rand::Rng;
-use rand_pcg::Pcg64;
-use rand_seeder::Seeder;
 
 use crate::{
     loading::RoomPaths,
     map::{
+        cells::{Cell, Coordinate},
         layers::{LayerType, StructureType},
-        Grid, Room, TopDownMap,
+        DoorPolicy, Grid, Room, TopDownMap,
     },
 };
 
-use super::{assets::RoomTemplates, GenerationError, ItemGeneration};
+use super::{
+    assets::RoomTemplates, locks::place_locks, place_doors, seed_version::PhaseRngs,
+    torches::place_torches, ConnectivityRepair, ExitConstraints, GenerationError, GenerationEvent,
+    GenerationPhase, GenerationReport, ItemGeneration, RoomRejection, SeedPhase, SeedVersion,
+};
 
 #[derive(Debug)]
 pub struct Generator {
@@ -24,7 +27,17 @@ pub struct Generator {
     pub target_hidden_items: Option<ItemGeneration>,
     pub target_items: Option<ItemGeneration>,
     pub include_outer_wall: bool,
-    rng: Pcg64,
+    pub exit_constraints: ExitConstraints,
+    pub connectivity_repair: ConnectivityRepair,
+    pub door_policy: DoorPolicy,
+    pub target_num_locks: usize,
+    pub entry_coordinate: Option<Coordinate>,
+    pub mask: Vec<Cell>,
+    pub blocked_coordinates: HashSet<Coordinate>,
+    pub pre_placed_rooms: Vec<Room>,
+    pub seed_version: SeedVersion,
+    pub torch_spacing: Option<NonZeroU16>,
+    rngs: Option<PhaseRngs>,
 }
 
 const DEFAULT_SEED: &str = "tdlg";
@@ -41,7 +54,17 @@ impl Default for Generator {
             target_hidden_items: Default::default(),
             target_items: Default::default(),
             include_outer_wall: true,
-            rng: Seeder::from(DEFAULT_SEED).make_rng(),
+            exit_constraints: Default::default(),
+            connectivity_repair: Default::default(),
+            door_policy: Default::default(),
+            target_num_locks: 0,
+            entry_coordinate: None,
+            mask: Vec::new(),
+            blocked_coordinates: HashSet::new(),
+            pre_placed_rooms: Vec::new(),
+            seed_version: SeedVersion::default(),
+            torch_spacing: None,
+            rngs: None,
         }
     }
 }
@@ -66,7 +89,17 @@ impl Generator {
             target_hidden_items,
             target_items,
             include_outer_wall,
-            rng: Seeder::from(seed).make_rng(),
+            exit_constraints: Default::default(),
+            connectivity_repair: Default::default(),
+            door_policy: Default::default(),
+            target_num_locks: 0,
+            entry_coordinate: None,
+            mask: Vec::new(),
+            blocked_coordinates: HashSet::new(),
+            pre_placed_rooms: Vec::new(),
+            seed_version: SeedVersion::default(),
+            torch_spacing: None,
+            rngs: None,
         }
     }
 
@@ -79,7 +112,17 @@ impl Generator {
             target_hidden_items: Default::default(),
             target_items: Default::default(),
             include_outer_wall: true,
-            rng: Seeder::from(seed).make_rng(),
+            exit_constraints: Default::default(),
+            connectivity_repair: Default::default(),
+            door_policy: Default::default(),
+            target_num_locks: 0,
+            entry_coordinate: None,
+            mask: Vec::new(),
+            blocked_coordinates: HashSet::new(),
+            pre_placed_rooms: Vec::new(),
+            seed_version: SeedVersion::default(),
+            torch_spacing: None,
+            rngs: None,
         }
     }
 
@@ -94,10 +137,10 @@ impl Generator {
             return Err(GenerationError::no_room_paths());
         }
 
-        let rooms: Vec<Room> = all_room_paths
-            .into_iter()
-            .flat_map(|path| path.load_rooms().into_iter().flatten())
-            .collect();
+        let mut rooms: Vec<Room> = Vec::new();
+        for path in all_room_paths {
+            rooms.extend(path.load_rooms()?);
+        }
 
         Ok(Generator {
             grid_size: NonZeroU16::new(DEFAULT_GRID_SIZE).unwrap(),
@@ -107,11 +150,61 @@ impl Generator {
             target_hidden_items,
             target_items,
             include_outer_wall,
-            rng: Seeder::from(seed).make_rng(),
+            exit_constraints: Default::default(),
+            connectivity_repair: Default::default(),
+            door_policy: Default::default(),
+            target_num_locks: 0,
+            entry_coordinate: None,
+            mask: Vec::new(),
+            blocked_coordinates: HashSet::new(),
+            pre_placed_rooms: Vec::new(),
+            seed_version: SeedVersion::default(),
+            torch_spacing: None,
+            rngs: None,
         })
     }
 
     pub fn generate_top_down_map(&mut self) -> Result<TopDownMap, GenerationError> {
+        self.generate_with_events(|_, _| {})
+    }
+
+    pub fn generate_with_report(
+        &mut self,
+    ) -> Result<(TopDownMap, GenerationReport), GenerationError> {
+        let mut report = GenerationReport::new(
+            &self.seed,
+            self.room_templates.rooms.len(),
+            self.target_hidden_items.as_ref(),
+            self.target_items.as_ref(),
+        );
+
+        let map = self.generate_with_events(|event, _| report.record(event))?;
+        report.finish(&map);
+
+        Ok((map, report))
+    }
+
+    pub fn generate_with_events<F: FnMut(&GenerationEvent, &Grid)>(
+        &mut self,
+        on_event: F,
+    ) -> Result<TopDownMap, GenerationError> {
+        // The RNGs outlive a single map, so generating again carries on from
+        // where the last map left off instead of repeating it.
+        let mut rngs = self
+            .rngs
+            .take()
+            .unwrap_or_else(|| PhaseRngs::new(self.seed_version, &self.seed));
+        let map = self.generate_with_rngs(&mut rngs, on_event);
+        self.rngs = Some(rngs);
+
+        map
+    }
+
+    fn generate_with_rngs<F: FnMut(&GenerationEvent, &Grid)>(
+        &self,
+        rngs: &mut PhaseRngs,
+        mut on_event: F,
+    ) -> Result<TopDownMap, GenerationError> {
         if self.room_templates.rooms.is_empty() {
             return Err(GenerationError::no_room_paths());
         }
@@ -119,125 +212,608 @@ impl Generator {
         let mut grid = Grid::build(self.grid_size.get(), self.seed.clone());
         let mut room_count = 0;
 
+        for cell in self.mask.iter() {
+            grid.replace_cell(cell.clone());
+            grid.block(cell.coordinate());
+        }
+
+        for coordinate in self.blocked_coordinates.iter() {
+            grid.block(coordinate);
+        }
+
+        if let Some(entry_coordinate) = &self.entry_coordinate {
+            if !grid.is_in_bounds(entry_coordinate) {
+                return Err(GenerationError::entry_out_of_bounds());
+            }
+            if grid.is_blocked(entry_coordinate) {
+                return Err(GenerationError::entry_blocked());
+            }
+        }
+
+        on_event(
+            &GenerationEvent::PhaseStarted(GenerationPhase::Rooms),
+            &grid,
+        );
+
+        for room in self.pre_placed_rooms.iter() {
+            if !room.cells().iter().all(|cell| {
+                grid.is_cell_empty(cell.coordinate()) && !grid.is_blocked(cell.coordinate())
+            }) {
+                return Err(GenerationError::pre_placed_room_blocked());
+            }
+
+            room_count += 1;
+            grid.add_room(room.clone());
+            on_event(
+                &GenerationEvent::RoomPlaced {
+                    template_index: None,
+                    coordinate: room.bounds().0,
+                },
+                &grid,
+            );
+        }
+
         for _ in 0..self.target_number_rooms.get() {
-            let index: usize = self.rng.gen_range(0..self.room_templates.rooms.len());
+            let index: usize = rngs
+                .rng(SeedPhase::Rooms)
+                .gen_range(0..self.room_templates.rooms.len());
             let template = self.room_templates.rooms.get(index).unwrap().clone();
             let max_side_length = template.max_side_length();
 
-            let x: i32 = self
-                .rng
+            let x: i32 = rngs
+                .rng(SeedPhase::Rooms)
                 .gen_range(1..=(self.grid_size.get() - max_side_length) as i32);
-            let y: i32 = self
-                .rng
+            let y: i32 = rngs
+                .rng(SeedPhase::Rooms)
                 .gen_range(1..=(self.grid_size.get() - max_side_length - 1) as i32);
             let mut room = template.translate(x, y);
+            let coordinate = Coordinate::from((x, y));
 
-            let door_cells = room.possible_door_cells();
-            if door_cells.is_empty() {
-                continue;
-            }
+            on_event(
+                &GenerationEvent::RoomAttempted {
+                    template_index: index,
+                    coordinate,
+                },
+                &grid,
+            );
 
-            let index = self.rng.gen_range(0..door_cells.len());
-            if let Some(cell) = door_cells.get(index) {
-                room.replace_cell_contents(
-                    cell.coordinate().x(),
-                    cell.coordinate().y(),
-                    LayerType::Structure(StructureType::Door),
-                );
+            let door_policy = template
+                .door_policy()
+                .cloned()
+                .unwrap_or_else(|| self.door_policy.clone());
+            let doors = if door_policy.keep_template_doors && room.has_doors() {
+                Vec::new()
             } else {
-                println!("did not find door {index} {door_cells:?}");
-            }
+                match place_doors(
+                    &mut room,
+                    &door_policy,
+                    grid.rooms(),
+                    rngs.rng(SeedPhase::Doors),
+                ) {
+                    Some(it) => it,
+                    None => {
+                        on_event(
+                            &GenerationEvent::RoomRejected {
+                                template_index: index,
+                                coordinate,
+                                reason: RoomRejection::NoDoorCells,
+                            },
+                            &grid,
+                        );
+                        continue;
+                    }
+                }
+            };
 
-            let roll_for_spawn: u8 = self.rng.gen_range(1..=100);
+            let mut table = None;
+            let roll_for_spawn: u8 = rngs.rng(SeedPhase::Rooms).gen_range(1..=100);
             if roll_for_spawn <= CHANCE_TO_SPAWN_IN_ROOM {
                 let spawnable_cells = room.spawnable_cells();
                 let spawn_index_range = 0..spawnable_cells.len();
                 if !spawn_index_range.is_empty() {
-                    if let Some(cell) = spawnable_cells.get(self.rng.gen_range(spawn_index_range)) {
-                        room.add_layer_to_cell(
-                            cell.coordinate().x(),
-                            cell.coordinate().y(),
-                            LayerType::Structure(StructureType::Table),
-                        );
+                    if let Some(cell) =
+                        spawnable_cells.get(rngs.rng(SeedPhase::Rooms).gen_range(spawn_index_range))
+                    {
+                        table = Some(*cell.coordinate());
                     }
                 }
             }
 
-            if room
-                .cells()
-                .iter()
-                .all(|cell| grid.is_cell_empty(cell.coordinate()))
-            {
-                room_count += 1;
-                grid.add_room(room);
+            if let Some(table) = &table {
+                room.add_layer_to_cell(
+                    table.x(),
+                    table.y(),
+                    LayerType::Structure(StructureType::Table),
+                );
+            }
+
+            let rejection = room.cells().iter().find_map(|cell| {
+                if grid.is_blocked(cell.coordinate()) {
+                    Some(RoomRejection::Blocked)
+                } else if !grid.is_cell_empty(cell.coordinate())
+                    || self.entry_coordinate.as_ref() == Some(cell.coordinate())
+                {
+                    Some(RoomRejection::Overlap)
+                } else {
+                    None
+                }
+            });
+
+            if let Some(reason) = rejection {
+                on_event(
+                    &GenerationEvent::RoomRejected {
+                        template_index: index,
+                        coordinate,
+                        reason,
+                    },
+                    &grid,
+                );
+                continue;
+            }
+
+            room_count += 1;
+            grid.add_room(room);
+            on_event(
+                &GenerationEvent::RoomPlaced {
+                    template_index: Some(index),
+                    coordinate,
+                },
+                &grid,
+            );
+
+            for door in doors.iter() {
+                if let Some(cell) = grid.cell(door) {
+                    on_event(
+                        &GenerationEvent::DoorPlaced {
+                            coordinate: *door,
+                            layer_type: cell.visible_layer(),
+                        },
+                        &grid,
+                    );
+                }
+            }
+
+            if let Some(table) = table {
+                on_event(&GenerationEvent::TablePlaced(table), &grid);
             }
         }
 
+        on_event(&GenerationEvent::PhaseStarted(GenerationPhase::Fill), &grid);
         grid.fill_empty_cells();
 
         if self.include_outer_wall {
             grid.create_outer_wall();
         }
 
-        let entry_coordinate = grid.random_spawnable_coordinate().unwrap();
+        on_event(
+            &GenerationEvent::PhaseStarted(GenerationPhase::Connectivity),
+            &grid,
+        );
+        self.connectivity_repair
+            .apply_around(&mut grid, self.entry_coordinate.as_ref());
+
+        // A forced entry is kept out of the item passes so nothing gets
+        // dropped on top of it.
+        let reserved_entry = self.entry_coordinate;
+        if let Some(coordinate) = &reserved_entry {
+            grid.block(coordinate);
+        }
+
+        let entry_coordinate = match self.entry_coordinate {
+            Some(it) => it,
+            None => rngs
+                .spawnable_coordinate(&mut grid, SeedPhase::EntryAndExit)
+                .ok_or_else(GenerationError::no_spawnable_cells)?,
+        };
 
+        on_event(
+            &GenerationEvent::PhaseStarted(GenerationPhase::HiddenItems),
+            &grid,
+        );
         if let Some(hidden_item_generation) = &self.target_hidden_items {
             for _ in 0..hidden_item_generation.target_num_items {
-                let coordinate = grid.random_unblocked_coordinate().unwrap();
-                let chance: usize = self.rng.gen_range(0..100);
+                let coordinate = rngs
+                    .unblocked_coordinate(&mut grid, SeedPhase::HiddenItems)
+                    .ok_or_else(GenerationError::no_spawnable_cells)?;
+                let chance: usize = rngs.rng(SeedPhase::HiddenItems).gen_range(0..100);
                 if let Some(it) = hidden_item_generation
                     .item_ranges
                     .iter()
                     .find(|hidden_chance| hidden_chance.chance.contains(&chance))
                 {
-                    grid.bury_layer(&coordinate, it.layer_type)
+                    grid.bury_layer(&coordinate, it.layer_type);
+                    on_event(
+                        &GenerationEvent::ItemBuried {
+                            coordinate,
+                            layer_type: it.layer_type,
+                        },
+                        &grid,
+                    );
+                } else {
+                    on_event(
+                        &GenerationEvent::ItemRollMissed {
+                            hidden: true,
+                            roll: chance,
+                        },
+                        &grid,
+                    );
                 }
             }
         }
 
+        on_event(
+            &GenerationEvent::PhaseStarted(GenerationPhase::Items),
+            &grid,
+        );
         if let Some(item_generation) = &self.target_items {
             for _ in 0..item_generation.target_num_items {
-                let coordinate = grid.random_spawnable_coordinate().unwrap();
-                let chance: usize = self.rng.gen_range(0..100);
+                let coordinate = rngs
+                    .spawnable_coordinate(&mut grid, SeedPhase::Items)
+                    .ok_or_else(GenerationError::no_spawnable_cells)?;
+                let chance: usize = rngs.rng(SeedPhase::Items).gen_range(0..100);
                 if let Some(it) = item_generation
                     .item_ranges
                     .iter()
                     .find(|item_chance| item_chance.chance.contains(&chance))
                 {
-                    grid.add_layer(&coordinate, it.layer_type)
+                    grid.add_layer(&coordinate, it.layer_type);
+                    on_event(
+                        &GenerationEvent::ItemPlaced {
+                            coordinate,
+                            layer_type: it.layer_type,
+                        },
+                        &grid,
+                    );
+                } else {
+                    on_event(
+                        &GenerationEvent::ItemRollMissed {
+                            hidden: false,
+                            roll: chance,
+                        },
+                        &grid,
+                    );
                 }
             }
         }
 
-        let exit_coordinate = grid.random_spawnable_coordinate().unwrap();
+        if let Some(coordinate) = &reserved_entry {
+            grid.unblock(coordinate);
+        }
+
+        on_event(
+            &GenerationEvent::PhaseStarted(GenerationPhase::EntryAndExit),
+            &grid,
+        );
+        let (entry_coordinate, exit_coordinate, exit_path) =
+            self.place_entry_and_exit(&mut grid, rngs, entry_coordinate, &mut on_event)?;
 
         grid.add_layer(&entry_coordinate, LayerType::Entrance);
+        on_event(&GenerationEvent::EntrancePlaced(entry_coordinate), &grid);
         grid.add_layer(&exit_coordinate, LayerType::Exit);
+        on_event(&GenerationEvent::ExitPlaced(exit_coordinate), &grid);
 
-        let result = astar(
-            &entry_coordinate,
-            |c| {
-                grid.surrounding_walkable_coordinates(c)
-                    .into_iter()
-                    .map(|c| (c, 1))
-            },
-            |c| c.distance(&exit_coordinate) / 3,
-            |c| c.eq(&exit_coordinate),
-        );
-
-        if let Some((exit_path, _)) = result {
+        on_event(&GenerationEvent::PhaseStarted(GenerationPhase::Path), &grid);
+        if let Some(exit_path) = &exit_path {
             for coordinate in exit_path.iter() {
-                if coordinate.ne(&entry_coordinate) && coordinate.ne(&exit_coordinate) {
+                if coordinate.ne(&entry_coordinate)
+                    && coordinate.ne(&exit_coordinate)
+                    && !grid.is_blocked(coordinate)
+                {
                     grid.add_layer(coordinate, LayerType::Path);
                 }
             }
         }
+        on_event(&GenerationEvent::PathComputed(exit_path.clone()), &grid);
 
-        Ok(TopDownMap::new(
-            grid,
-            room_count,
-            entry_coordinate,
-            exit_coordinate,
-        ))
+        on_event(
+            &GenerationEvent::PhaseStarted(GenerationPhase::Locks),
+            &grid,
+        );
+        let locks = match &exit_path {
+            Some(path) => place_locks(
+                &mut grid,
+                path,
+                self.target_num_locks,
+                rngs.rng(SeedPhase::Locks),
+            ),
+            None => Vec::new(),
+        };
+        for lock_and_key in locks.iter() {
+            on_event(&GenerationEvent::LockPlaced(*lock_and_key), &grid);
+        }
+
+        if let Some(torch_spacing) = self.torch_spacing {
+            on_event(
+                &GenerationEvent::PhaseStarted(GenerationPhase::Torches),
+                &grid,
+            );
+            for torch in place_torches(&mut grid, torch_spacing) {
+                on_event(&GenerationEvent::TorchPlaced(torch), &grid);
+            }
+        }
+
+        let mut map = TopDownMap::new(grid, room_count, entry_coordinate, exit_coordinate);
+        map.set_path(exit_path);
+        map.set_locks(locks);
+
+        Ok(map)
+    }
+
+    fn place_entry_and_exit<F: FnMut(&GenerationEvent, &Grid)>(
+        &self,
+        grid: &mut Grid,
+        rngs: &mut PhaseRngs,
+        first_entry: Coordinate,
+        on_event: &mut F,
+    ) -> Result<(Coordinate, Coordinate, Option<Vec<Coordinate>>), GenerationError> {
+        let mut entry_coordinate = first_entry;
+
+        for attempt in 0..self.exit_constraints.max_attempts.get() {
+            if attempt > 0 && self.entry_coordinate.is_none() {
+                entry_coordinate = rngs
+                    .spawnable_coordinate(grid, SeedPhase::EntryAndExit)
+                    .ok_or_else(GenerationError::no_spawnable_cells)?;
+            }
+
+            let exit_coordinate = rngs
+                .spawnable_coordinate(grid, SeedPhase::EntryAndExit)
+                .ok_or_else(GenerationError::no_spawnable_cells)?;
+            let exit_path = find_path(grid, &entry_coordinate, &exit_coordinate);
+
+            if self.exit_constraints.is_satisfied_by(
+                grid,
+                &entry_coordinate,
+                &exit_coordinate,
+                exit_path.as_ref(),
+            ) {
+                return Ok((entry_coordinate, exit_coordinate, exit_path));
+            }
+
+            on_event(
+                &GenerationEvent::ExitRejected {
+                    entry: entry_coordinate,
+                    exit: exit_coordinate,
+                },
+                grid,
+            );
+        }
+
+        Err(GenerationError::exit_constraints_unsatisfied())
+    }
+}
+
+fn find_path(grid: &Grid, start: &Coordinate, end: &Coordinate) -> Option<Vec<Coordinate>> {
+    astar(
+        start,
+        |c| {
+            grid.surrounding_walkable_coordinates(c)
+                .into_iter()
+                .map(|c| (c, 1))
+        },
+        |c| c.distance(end) / 3,
+        |c| c.eq(end),
+    )
+    .map(|(path, _)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, num::NonZeroU16};
+
+    use crate::{
+        generation::{
+            builder, ConnectivityRepair, GenerationEvent, GenerationPhase, ItemChance,
+            ItemGeneration, RoomRejection,
+        },
+        map::{
+            cells::Coordinate,
+            layers::{ItemRarity, LayerType, StructureType},
+            Grid, Room,
+        },
+    };
+
+    const GRID_SIZE: u16 = 40;
+
+    fn outside_island() -> HashSet<Coordinate> {
+        (0..GRID_SIZE as i32)
+            .flat_map(|x| (0..GRID_SIZE as i32).map(move |y| Coordinate::from((x, y))))
+            .filter(|coordinate| coordinate.distance(&Coordinate::from(20)) > 18)
+            .collect()
+    }
+
+    #[test]
+    fn blocked_coordinates_are_left_untouched() {
+        let blocked = outside_island();
+        let mut generator = builder()
+            .seed("island")
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .blocked_coordinates(blocked.clone())
+            .target_items(ItemGeneration {
+                target_num_items: 30,
+                item_ranges: vec![ItemChance {
+                    layer_type: LayerType::Item(ItemRarity::Common),
+                    chance: 0..100,
+                }],
+            })
+            .build();
+
+        let map = generator.generate_top_down_map().unwrap();
+
+        for coordinate in blocked.iter() {
+            assert!(map.grid().cell(coordinate).unwrap().is_empty());
+        }
+        assert!(!blocked.contains(map.entry()));
+        assert!(!blocked.contains(map.exit()));
+    }
+
+    #[test]
+    fn grids_without_room_to_spawn_are_an_error() {
+        let mut mask = Grid::build(GRID_SIZE, "mask".to_string());
+        let everything: HashSet<Coordinate> = mask
+            .cells()
+            .into_iter()
+            .map(|cell| *cell.coordinate())
+            .collect();
+        for coordinate in everything.iter() {
+            mask.add_layer(coordinate, LayerType::Structure(StructureType::Rocks));
+        }
+
+        let masked = builder()
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .mask(&mask)
+            .build()
+            .generate_top_down_map();
+        assert_eq!(masked.unwrap_err().message, "no_spawnable_cells");
+
+        let blocked = builder()
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .blocked_coordinates(everything)
+            .build()
+            .generate_top_down_map();
+        assert_eq!(blocked.unwrap_err().message, "no_spawnable_cells");
+
+        let mut grid = Grid::build(3, "empty".to_string());
+        assert_eq!(grid.random_spawnable_coordinate(), None);
+        for coordinate in grid.unblocked_coordinates() {
+            grid.block(&coordinate);
+        }
+        assert_eq!(grid.random_unblocked_coordinate(), None);
+    }
+
+    #[test]
+    fn forced_entry_is_left_clear() {
+        let items = ItemGeneration {
+            target_num_items: 400,
+            item_ranges: vec![ItemChance {
+                layer_type: LayerType::Item(ItemRarity::Common),
+                chance: 0..100,
+            }],
+        };
+
+        for seed in ["forced", "stairs", "landing"] {
+            let entry = Coordinate::from((3, 3));
+            let map = builder()
+                .seed(seed)
+                .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+                .entry_coordinate(entry)
+                .connectivity_repair(ConnectivityRepair::Fill)
+                .target_hidden_items(items.clone())
+                .target_items(items.clone())
+                .build()
+                .generate_top_down_map()
+                .unwrap();
+
+            let layers = map.grid().cell(&entry).unwrap().layers();
+            assert_eq!(map.entry(), &entry);
+            assert!(matches!(layers[0], LayerType::Floor(_)), "{}", seed);
+            assert_eq!(&layers[1..], &[LayerType::Entrance], "{}", seed);
+            assert!(!map.grid().is_blocked(&entry));
+        }
+    }
+
+    #[test]
+    fn forced_entry_must_be_open() {
+        let entry = Coordinate::from((3, 3));
+        let mut mask = Grid::build(GRID_SIZE, "mask".to_string());
+        mask.add_layer(&entry, LayerType::Structure(StructureType::Rocks));
+
+        let masked = builder()
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .entry_coordinate(entry)
+            .mask(&mask)
+            .build()
+            .generate_top_down_map();
+        assert_eq!(masked.unwrap_err().message, "entry_blocked");
+
+        let blocked = builder()
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .entry_coordinate(entry)
+            .blocked_coordinates(HashSet::from([entry]))
+            .build()
+            .generate_top_down_map();
+        assert_eq!(blocked.unwrap_err().message, "entry_blocked");
+
+        let outside = builder()
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .entry_coordinate(Coordinate::from((GRID_SIZE as i32, 0)))
+            .build()
+            .generate_top_down_map();
+        assert_eq!(outside.unwrap_err().message, "entry_out_of_bounds");
+    }
+
+    #[test]
+    fn mask_content_and_pre_placed_rooms_are_kept() {
+        let mut mask = Grid::build(GRID_SIZE, "mask".to_string());
+        for x in 0..GRID_SIZE as i32 {
+            mask.add_layer(
+                &Coordinate::from((x, 0)),
+                LayerType::Structure(StructureType::Rocks),
+            );
+        }
+        let keep = Room::from("||||\n|==D\n||||").translate(10, 10);
+
+        let mut generator = builder()
+            .seed("castle")
+            .grid_size(NonZeroU16::new(GRID_SIZE).unwrap())
+            .mask(&mask)
+            .pre_placed_rooms(vec![keep.clone()])
+            .build();
+
+        let map = generator.generate_top_down_map().unwrap();
+
+        for x in 0..GRID_SIZE as i32 {
+            assert_eq!(
+                map.grid().cell(&Coordinate::from((x, 0))).unwrap().layers(),
+                &vec![LayerType::Structure(StructureType::Rocks)]
+            );
+        }
+        assert_eq!(map.grid().rooms()[0].cells(), keep.cells());
+        assert_eq!(
+            map.grid()
+                .cell(&Coordinate::from((13, 11)))
+                .unwrap()
+                .layers()
+                .first(),
+            Some(&LayerType::Structure(StructureType::Door))
+        );
+    }
+
+    #[test]
+    fn generating_with_events_builds_the_same_map() {
+        let mut generator = builder().seed("step by step").build();
+        let expected = generator.generate_top_down_map().unwrap();
+
+        let mut events: Vec<GenerationEvent> = Vec::new();
+        let mut placed_rooms_seen = 0;
+        let mut generator = builder().seed("step by step").build();
+        let map = generator
+            .generate_with_events(|event, grid| {
+                if let GenerationEvent::RoomPlaced { .. } = event {
+                    placed_rooms_seen += 1;
+                    assert_eq!(grid.rooms().len(), placed_rooms_seen);
+                }
+                events.push(event.clone());
+            })
+            .unwrap();
+
+        assert_eq!(
+            map.grid().top_layer_display(),
+            expected.grid().top_layer_display()
+        );
+        assert_eq!(placed_rooms_seen, map.room_count());
+        assert_eq!(
+            events.first(),
+            Some(&GenerationEvent::PhaseStarted(GenerationPhase::Rooms))
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GenerationEvent::RoomRejected {
+                reason: RoomRejection::Overlap,
+                ..
+            }
+        )));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GenerationEvent::PathComputed(Some(_)))));
     }
 }
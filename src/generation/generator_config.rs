@@ -0,0 +1,458 @@
+use std::num::NonZeroU16;
+#[cfg(feature = "config")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    loading::RoomPaths,
+    map::{cells::Coordinate, DoorPolicy, Room},
+};
+
+use super::{
+    assets::RoomTemplates, builder, ConnectivityRepair, ExitConstraints, GenerationError,
+    Generator, GeneratorBuilder, ItemGeneration, SeedVersion,
+};
+
+#[cfg(feature = "config")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Ron,
+}
+
+#[cfg(feature = "config")]
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            "ron" => Some(ConfigFormat::Ron),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratorConfig {
+    pub seed: String,
+    pub seed_version: SeedVersion,
+    pub grid_size: u16,
+    pub target_number_rooms: u16,
+    pub room_paths: Vec<RoomPaths>,
+    pub target_hidden_items: Option<ItemGeneration>,
+    pub target_items: Option<ItemGeneration>,
+    pub include_outer_wall: bool,
+    pub exit_constraints: ExitConstraints,
+    pub connectivity_repair: ConnectivityRepair,
+    pub door_policy: DoorPolicy,
+    pub target_num_locks: usize,
+    pub entry_coordinate: Option<Coordinate>,
+    pub torch_spacing: Option<NonZeroU16>,
+}
+
+const DEFAULT_SEED: &str = "tdlg";
+const DEFAULT_GRID_SIZE: u16 = 100;
+const DEFAULT_TARGET_NUMBER_ROOMS: u16 = 25;
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: DEFAULT_SEED.to_string(),
+            seed_version: SeedVersion::default(),
+            grid_size: DEFAULT_GRID_SIZE,
+            target_number_rooms: DEFAULT_TARGET_NUMBER_ROOMS,
+            room_paths: Vec::new(),
+            target_hidden_items: None,
+            target_items: None,
+            include_outer_wall: false,
+            exit_constraints: ExitConstraints::default(),
+            connectivity_repair: ConnectivityRepair::default(),
+            door_policy: DoorPolicy::default(),
+            target_num_locks: 0,
+            entry_coordinate: None,
+            torch_spacing: None,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    #[cfg(feature = "config")]
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, GenerationError> {
+        let config: Self = match format {
+            ConfigFormat::Toml => {
+                toml::from_str(content).map_err(|error| error.message().to_string())
+            }
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|error| error.to_string()),
+            ConfigFormat::Ron => ron::from_str(content).map_err(|error| error.to_string()),
+        }
+        .map_err(|error| GenerationError::config_cannot_be_parsed(&error))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(feature = "config")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GenerationError> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            GenerationError::config_cannot_be_read(&format!(
+                "{} is not a .toml, .json or .ron file",
+                path.display()
+            ))
+        })?;
+        let content = fs::read_to_string(path).map_err(|error| {
+            GenerationError::config_cannot_be_read(&format!("{}: {}", path.display(), error))
+        })?;
+
+        let mut config = Self::parse(&content, format)?;
+
+        // Template paths are written relative to the config file, not to
+        // wherever the game happens to be running from.
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        for room_paths in config.room_paths.iter_mut() {
+            let template_path = PathBuf::from(&room_paths.template_path);
+            if template_path.is_relative() {
+                room_paths.template_path = directory.join(template_path).display().to_string();
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), GenerationError> {
+        if self.grid_size == 0 {
+            return Err(GenerationError::invalid_config(
+                "grid_size must be greater than 0",
+            ));
+        }
+
+        if self.target_number_rooms == 0 {
+            return Err(GenerationError::invalid_config(
+                "target_number_rooms must be greater than 0",
+            ));
+        }
+
+        for (name, item_generation) in [
+            ("target_hidden_items", &self.target_hidden_items),
+            ("target_items", &self.target_items),
+        ] {
+            if let Some(item_generation) = item_generation {
+                Self::validate_items(name, item_generation)?;
+            }
+        }
+
+        if let (Some(min), Some(max)) = (
+            self.exit_constraints.min_path_length,
+            self.exit_constraints.max_path_length,
+        ) {
+            if min > max {
+                return Err(GenerationError::invalid_config(&format!(
+                    "exit_constraints.min_path_length ({}) is greater than max_path_length ({})",
+                    min, max
+                )));
+            }
+        }
+
+        if self.door_policy.door_count.start() > self.door_policy.door_count.end() {
+            return Err(GenerationError::invalid_config(
+                "door_policy.door_count must not be an empty range",
+            ));
+        }
+
+        let door_chances = self.door_policy.open_chance as u16
+            + self.door_policy.locked_chance as u16
+            + self.door_policy.secret_chance as u16;
+        if door_chances > 100 {
+            return Err(GenerationError::invalid_config(&format!(
+                "door_policy open, locked and secret chances add up to {}, more than 100",
+                door_chances
+            )));
+        }
+
+        if let Some(entry) = &self.entry_coordinate {
+            let size = self.grid_size as i32;
+            if !(0..size).contains(&entry.x()) || !(0..size).contains(&entry.y()) {
+                return Err(GenerationError::invalid_config(&format!(
+                    "entry_coordinate ({}, {}) is outside of the {}x{} grid",
+                    entry.x(),
+                    entry.y(),
+                    size,
+                    size
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_items(name: &str, item_generation: &ItemGeneration) -> Result<(), GenerationError> {
+        for (index, item_chance) in item_generation.item_ranges.iter().enumerate() {
+            if item_chance.chance.is_empty() || item_chance.chance.end > 100 {
+                return Err(GenerationError::invalid_config(&format!(
+                    "{}.item_ranges[{}] chance {}..{} must be a non-empty range within 0..100",
+                    name, index, item_chance.chance.start, item_chance.chance.end
+                )));
+            }
+
+            if let Some(other) = item_generation.item_ranges[..index]
+                .iter()
+                .position(|other| {
+                    other.chance.start < item_chance.chance.end
+                        && item_chance.chance.start < other.chance.end
+                })
+            {
+                return Err(GenerationError::invalid_config(&format!(
+                    "{}.item_ranges[{}] overlaps {}.item_ranges[{}]",
+                    name, index, name, other
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn room_templates(&self) -> Result<RoomTemplates, GenerationError> {
+        let room_templates = if self.room_paths.is_empty() {
+            RoomTemplates::default()
+        } else {
+            let mut rooms: Vec<Room> = Vec::new();
+            for room_paths in self.room_paths.iter() {
+                let loaded = room_paths.load_rooms()?;
+                if loaded.is_empty() {
+                    return Err(GenerationError::room_templates_cannot_be_loaded());
+                }
+                rooms.extend(loaded);
+            }
+            RoomTemplates { rooms }
+        };
+
+        // Rooms are stamped at least one cell in from the edge of the grid.
+        if room_templates
+            .rooms
+            .iter()
+            .any(|room| room.max_side_length() + 1 >= self.grid_size)
+        {
+            return Err(GenerationError::room_templates_too_large());
+        }
+
+        Ok(room_templates)
+    }
+
+    pub fn builder(&self) -> Result<GeneratorBuilder, GenerationError> {
+        self.validate()?;
+
+        let mut builder = builder();
+        builder
+            .seed(&self.seed)
+            .seed_version(self.seed_version)
+            .grid_size(NonZeroU16::new(self.grid_size).unwrap())
+            .target_number_rooms(NonZeroU16::new(self.target_number_rooms).unwrap())
+            .room_templates(self.room_templates()?)
+            .include_outer_wall(self.include_outer_wall)
+            .exit_constraints(self.exit_constraints.clone())
+            .connectivity_repair(self.connectivity_repair)
+            .door_policy(self.door_policy.clone())
+            .target_num_locks(self.target_num_locks);
+
+        if let Some(target_hidden_items) = &self.target_hidden_items {
+            builder.target_hidden_items(target_hidden_items.clone());
+        }
+        if let Some(target_items) = &self.target_items {
+            builder.target_items(target_items.clone());
+        }
+        if let Some(entry_coordinate) = self.entry_coordinate {
+            builder.entry_coordinate(entry_coordinate);
+        }
+        if let Some(torch_spacing) = self.torch_spacing {
+            builder.torch_spacing(torch_spacing);
+        }
+
+        Ok(builder)
+    }
+
+    pub fn generator(&self) -> Result<Generator, GenerationError> {
+        Ok(self.builder()?.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    #[cfg(feature = "config")]
+    use crate::generation::{ConfigFormat, ConnectivityRepair, SeedVersion};
+    use crate::{generation::GeneratorConfig, loading::RoomPaths};
+
+    #[cfg(feature = "config")]
+    const TOML: &str = r#"
+        seed = "from a file"
+        seed_version = "v2"
+        grid_size = 40
+        target_number_rooms = 10
+        include_outer_wall = true
+        connectivity_repair = "carve"
+
+        [target_items]
+        target_num_items = 5
+        item_ranges = [
+            { layer_type = { item = "common" }, chance = { start = 0, end = 80 } },
+            { layer_type = { item = "rare" }, chance = { start = 80, end = 100 } },
+        ]
+
+        [exit_constraints]
+        require_reachable = true
+
+        [door_policy]
+        door_count = { start = 1, end = 2 }
+    "#;
+
+    #[cfg(feature = "config")]
+    const JSON: &str = r#"{
+        "seed": "from a file",
+        "seed_version": "v2",
+        "grid_size": 40,
+        "target_number_rooms": 10,
+        "include_outer_wall": true,
+        "connectivity_repair": "carve",
+        "target_items": {
+            "target_num_items": 5,
+            "item_ranges": [
+                { "layer_type": { "item": "common" }, "chance": { "start": 0, "end": 80 } },
+                { "layer_type": { "item": "rare" }, "chance": { "start": 80, "end": 100 } }
+            ]
+        },
+        "exit_constraints": { "require_reachable": true },
+        "door_policy": { "door_count": { "start": 1, "end": 2 } }
+    }"#;
+
+    #[cfg(feature = "config")]
+    const RON: &str = r#"(
+        seed: "from a file",
+        seed_version: v2,
+        grid_size: 40,
+        target_number_rooms: 10,
+        include_outer_wall: true,
+        connectivity_repair: carve,
+        target_items: Some((
+            target_num_items: 5,
+            item_ranges: [
+                (layer_type: item(common), chance: (start: 0, end: 80)),
+                (layer_type: item(rare), chance: (start: 80, end: 100)),
+            ],
+        )),
+        exit_constraints: (require_reachable: true),
+        door_policy: (door_count: (start: 1, end: 2)),
+    )"#;
+
+    #[test]
+    #[cfg(feature = "config")]
+    fn formats_build_the_same_map() {
+        let maps: Vec<String> = [
+            (TOML, ConfigFormat::Toml),
+            (JSON, ConfigFormat::Json),
+            (RON, ConfigFormat::Ron),
+        ]
+        .into_iter()
+        .map(|(content, format)| {
+            let config = GeneratorConfig::parse(content, format).unwrap();
+            assert_eq!(config.seed_version, SeedVersion::V2);
+            assert_eq!(config.connectivity_repair, ConnectivityRepair::Carve);
+
+            config
+                .generator()
+                .unwrap()
+                .generate_top_down_map()
+                .unwrap()
+                .grid()
+                .top_layer_display()
+        })
+        .collect();
+
+        assert_eq!(maps[0], maps[1]);
+        assert_eq!(maps[0], maps[2]);
+    }
+
+    #[test]
+    #[cfg(feature = "config")]
+    fn invalid_configs_explain_themselves() {
+        let error = GeneratorConfig::parse("grid_size = 0", ConfigFormat::Toml).unwrap_err();
+        assert_eq!(
+            error.message,
+            "invalid_config: grid_size must be greater than 0"
+        );
+
+        let error = GeneratorConfig::parse(
+            r#"
+            [target_items]
+            target_num_items = 1
+            item_ranges = [
+                { layer_type = { item = "common" }, chance = { start = 0, end = 60 } },
+                { layer_type = { item = "rare" }, chance = { start = 50, end = 100 } },
+            ]
+            "#,
+            ConfigFormat::Toml,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.message,
+            "invalid_config: target_items.item_ranges[1] overlaps target_items.item_ranges[0]"
+        );
+
+        let error = GeneratorConfig::parse("grid_sise = 10", ConfigFormat::Toml).unwrap_err();
+        assert!(error
+            .message
+            .starts_with("config_cannot_be_parsed: unknown field `grid_sise`"));
+    }
+
+    #[test]
+    fn templates_must_fit_the_grid() {
+        let error = GeneratorConfig {
+            grid_size: 8,
+            ..Default::default()
+        }
+        .generator()
+        .unwrap_err();
+        assert_eq!(error.message, "room_templates_too_large");
+    }
+
+    #[test]
+    fn template_header_errors_are_returned() {
+        let directory = env::temp_dir().join("tdlg-config-bad-header");
+        fs::create_dir_all(&directory).unwrap();
+        let template = directory.join("room.txt");
+        fs::write(&template, "@doors = 2\n||||\n|==d\n||||").unwrap();
+
+        let error = GeneratorConfig {
+            room_paths: vec![RoomPaths {
+                name: "bad".to_string(),
+                template_path: directory.to_str().unwrap().to_string(),
+            }],
+            ..Default::default()
+        }
+        .generator()
+        .unwrap_err();
+        assert_eq!(
+            error.message,
+            format!(
+                "invalid_room_template: {}: unknown door policy key doors",
+                template.display()
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "config")]
+    fn loads_from_file_with_relative_templates() {
+        let config = GeneratorConfig::load("assets/config/example.toml").unwrap();
+
+        assert_eq!(config.room_paths[0].template_path, "assets/config/rooms");
+        let map = config.generator().unwrap().generate_top_down_map().unwrap();
+        assert!(map.room_count() > 0);
+    }
+}
@@ -1,14 +1,16 @@
 use std::ops::Range;
 
+use serde::{Deserialize, Serialize};
+
 use crate::map::layers::LayerType;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ItemChance {
     pub layer_type: LayerType,
     pub chance: Range<usize>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ItemGeneration {
     pub target_num_items: usize,
     pub item_ranges: Vec<ItemChance>,
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::map::{
+    cells::Coordinate,
+    layers::{LayerType, StructureType},
+    Grid, LockAndKey,
+};
+
+pub(crate) fn place_locks<R: Rng>(
+    grid: &mut Grid,
+    path: &[Coordinate],
+    target_num_locks: usize,
+    rng: &mut R,
+) -> Vec<LockAndKey> {
+    let (entry, exit) = match (path.first(), path.last()) {
+        (Some(entry), Some(exit)) if target_num_locks > 0 => (*entry, *exit),
+        _ => return Vec::new(),
+    };
+
+    // Only doors that cut the exit off from the entrance are worth locking,
+    // anything else could just be walked around. This works on single cells
+    // rather than a graph of rooms: only plain doors on the path are looked
+    // at, and a door only counts if closing that one cell is enough, so two
+    // doors into the same room never make a chokepoint together.
+    let mut chokepoints: Vec<Coordinate> = path
+        .iter()
+        .filter(|coordinate| {
+            !grid.is_blocked(coordinate)
+                && grid.cell(coordinate).is_some_and(|cell| {
+                    cell.layers()
+                        .contains(&LayerType::Structure(StructureType::Door))
+                })
+        })
+        .filter(|door| !reachable(grid, &entry, &HashSet::from([**door])).contains(&exit))
+        .copied()
+        .collect();
+
+    while chokepoints.len() > target_num_locks {
+        let index = rng.gen_range(0..chokepoints.len());
+        chokepoints.remove(index);
+    }
+
+    let mut locks: Vec<LockAndKey> = Vec::new();
+
+    for (index, lock) in chokepoints.iter().enumerate() {
+        let blocked: HashSet<Coordinate> = chokepoints[index..].iter().copied().collect();
+        let mut key_cells: Vec<Coordinate> = reachable(grid, &entry, &blocked)
+            .into_iter()
+            .filter(|coordinate| {
+                !grid.is_blocked(coordinate)
+                    && grid
+                        .cell(coordinate)
+                        .is_some_and(|cell| cell.is_spawnable())
+            })
+            .collect();
+        key_cells.sort();
+
+        if key_cells.is_empty() {
+            continue;
+        }
+
+        let key = key_cells[rng.gen_range(0..key_cells.len())];
+        grid.add_layer(&key, LayerType::Key);
+        locks.push(LockAndKey { lock: *lock, key });
+    }
+
+    for lock_and_key in locks.iter() {
+        grid.replace_layer(
+            &lock_and_key.lock,
+            &LayerType::Structure(StructureType::Door),
+            LayerType::Structure(StructureType::LockedDoor),
+        );
+        grid.remove_layer(&lock_and_key.lock, LayerType::Path);
+    }
+
+    locks
+}
+
+fn reachable(
+    grid: &Grid,
+    start: &Coordinate,
+    blocked: &HashSet<Coordinate>,
+) -> HashSet<Coordinate> {
+    let mut reachable: HashSet<Coordinate> = HashSet::from([*start]);
+    let mut frontier = vec![*start];
+
+    while let Some(coordinate) = frontier.pop() {
+        for neighbour in grid.surrounding_walkable_coordinates(&coordinate) {
+            if !blocked.contains(&neighbour) && reachable.insert(neighbour) {
+                frontier.push(neighbour);
+            }
+        }
+    }
+
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generation::{builder, ExitConstraints},
+        map::layers::{FloorType, LayerType, StructureType},
+    };
+
+    #[test]
+    fn locked_levels_are_solvable() {
+        let mut generator = builder()
+            .seed("lock and key")
+            .exit_constraints(ExitConstraints {
+                entry_floor: Some(FloorType::Outdoor),
+                exit_floor: Some(FloorType::Indoor),
+                ..Default::default()
+            })
+            .target_num_locks(2)
+            .build();
+
+        let map = generator.generate_top_down_map().unwrap();
+
+        assert!(!map.locks().is_empty());
+        assert!(map.is_solvable());
+
+        for lock_and_key in map.locks() {
+            let lock = map.grid().cell(&lock_and_key.lock).unwrap();
+            let key = map.grid().cell(&lock_and_key.key).unwrap();
+
+            assert_eq!(
+                lock.visible_layer(),
+                LayerType::Structure(StructureType::LockedDoor)
+            );
+            assert_eq!(key.visible_layer(), LayerType::Key);
+            assert!(map.path().unwrap().contains(&lock_and_key.lock));
+        }
+
+        // The stored path runs unbroken through the locked doors.
+        let path = map.path().unwrap();
+        assert_eq!(path.first(), Some(map.entry()));
+        assert_eq!(path.last(), Some(map.exit()));
+        assert!(path.windows(2).all(|pair| pair[0].distance(&pair[1]) == 1));
+
+        let distances = map.grid().distance_map(&[*map.entry()]);
+        assert!(!distances.is_reachable(map.exit()));
+    }
+
+    #[test]
+    fn maps_without_locks_are_solvable() {
+        let mut generator = builder()
+            .seed("no locks")
+            .exit_constraints(ExitConstraints {
+                require_reachable: true,
+                ..Default::default()
+            })
+            .build();
+
+        let map = generator.generate_top_down_map().unwrap();
+
+        assert!(map.locks().is_empty());
+        assert!(map.is_solvable());
+    }
+}
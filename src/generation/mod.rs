@@ -1,10 +1,32 @@
 pub mod assets;
 mod builder;
+mod connectivity;
+mod doors;
+mod dungeon;
 mod error;
+mod exit_constraints;
+mod generation_event;
+mod generation_report;
 mod generator;
+mod generator_config;
 mod item_generation;
+mod locks;
+mod seed_search;
+mod seed_version;
+mod torches;
 
 pub use builder::{builder, GeneratorBuilder};
+pub use connectivity::ConnectivityRepair;
+pub(crate) use doors::place_doors;
+pub use dungeon::Dungeon;
 pub use error::GenerationError;
+pub use exit_constraints::ExitConstraints;
+pub use generation_event::{GenerationEvent, GenerationPhase, RoomRejection};
+pub use generation_report::{GenerationReport, ItemStats, LayerCount, PhaseTiming, TemplateStats};
 pub use generator::Generator;
+#[cfg(feature = "config")]
+pub use generator_config::ConfigFormat;
+pub use generator_config::GeneratorConfig;
 pub use item_generation::{ItemChance, ItemGeneration};
+pub use seed_search::{SeedScore, SeedSearch};
+pub use seed_version::{SeedPhase, SeedVersion};
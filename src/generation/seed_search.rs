@@ -0,0 +1,205 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::map::TopDownMap;
+
+use super::{GenerationError, GeneratorBuilder};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedScore {
+    pub seed: String,
+    pub score: f64,
+}
+
+#[derive(Clone)]
+pub struct SeedSearch {
+    base: GeneratorBuilder,
+    threads: NonZeroUsize,
+}
+
+impl SeedSearch {
+    pub fn new(base: GeneratorBuilder) -> Self {
+        Self {
+            base,
+            threads: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        }
+    }
+
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn threads(&self) -> NonZeroUsize {
+        self.threads
+    }
+
+    pub fn generate<S: ToString>(
+        &self,
+        seeds: impl IntoIterator<Item = S>,
+    ) -> Vec<(String, Result<TopDownMap, GenerationError>)> {
+        let seeds: Vec<String> = seeds.into_iter().map(|seed| seed.to_string()).collect();
+        let results = self.map_seeds(&seeds, |result| result);
+
+        seeds.into_iter().zip(results).collect()
+    }
+
+    pub fn best_seeds<S, F>(
+        &self,
+        seeds: impl IntoIterator<Item = S>,
+        count: usize,
+        score: F,
+    ) -> Vec<SeedScore>
+    where
+        S: ToString,
+        F: Fn(&TopDownMap) -> Option<f64> + Sync,
+    {
+        let seeds: Vec<String> = seeds.into_iter().map(|seed| seed.to_string()).collect();
+        let scores = self.map_seeds(&seeds, |result| result.ok().and_then(|map| score(&map)));
+
+        let mut best: Vec<SeedScore> = seeds
+            .into_iter()
+            .zip(scores)
+            .filter_map(|(seed, score)| score.map(|score| SeedScore { seed, score }))
+            .collect();
+        // Stable, so seeds with equal scores stay in the order they were given.
+        best.sort_by(|a, b| b.score.total_cmp(&a.score));
+        best.truncate(count);
+        best
+    }
+
+    pub fn matching_seeds<S, F>(
+        &self,
+        seeds: impl IntoIterator<Item = S>,
+        predicate: F,
+    ) -> Vec<String>
+    where
+        S: ToString,
+        F: Fn(&TopDownMap) -> bool + Sync,
+    {
+        let seeds: Vec<String> = seeds.into_iter().map(|seed| seed.to_string()).collect();
+        let matches = self.map_seeds(&seeds, |result| {
+            result.ok().is_some_and(|map| predicate(&map)).then_some(())
+        });
+
+        seeds
+            .into_iter()
+            .zip(matches)
+            .filter_map(|(seed, matched)| matched.map(|_| seed))
+            .collect()
+    }
+
+    // Each seed gets its own generator, so threads just pull the next seed off
+    // a shared counter until every seed has been generated.
+    fn map_seeds<T, F>(&self, seeds: &[String], evaluate: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(Result<TopDownMap, GenerationError>) -> T + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<T>>> = Mutex::new((0..seeds.len()).map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.get().min(seeds.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(seed) = seeds.get(index) else {
+                        break;
+                    };
+
+                    let mut builder = self.base.clone();
+                    builder.seed(seed);
+                    let value = evaluate(builder.build().generate_top_down_map());
+
+                    results.lock().unwrap()[index] = Some(value);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU16, NonZeroUsize};
+
+    use crate::generation::builder;
+
+    use super::SeedSearch;
+
+    fn search() -> SeedSearch {
+        let mut base = builder();
+        base.grid_size(NonZeroU16::new(40).unwrap());
+
+        SeedSearch::new(base).with_threads(NonZeroUsize::new(4).unwrap())
+    }
+
+    #[test]
+    fn batch_generation_matches_single_generation() {
+        let maps = search().generate(["a", "b", "c"]);
+
+        assert_eq!(
+            maps.iter()
+                .map(|(seed, _)| seed.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["a", "b", "c"]
+        );
+
+        for (seed, map) in maps {
+            let expected = builder()
+                .grid_size(NonZeroU16::new(40).unwrap())
+                .seed(&seed)
+                .build()
+                .generate_top_down_map()
+                .unwrap();
+
+            assert_eq!(
+                map.unwrap().grid().top_layer_display(),
+                expected.grid().top_layer_display()
+            );
+        }
+    }
+
+    #[test]
+    fn best_seeds_are_sorted_by_score() {
+        let best = search().best_seeds(0..20, 5, |map| Some(map.room_count() as f64));
+
+        assert_eq!(best.len(), 5);
+        assert!(best.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+        let all = search().best_seeds(0..20, 20, |map| Some(map.room_count() as f64));
+        assert_eq!(best[0].score, all[0].score);
+    }
+
+    #[test]
+    fn predicates_filter_seeds() {
+        let matching = search().matching_seeds(0..20, |map| {
+            map.room_count() >= 5 && map.path_length().is_some_and(|length| length > 20)
+        });
+
+        assert!(!matching.is_empty());
+        for seed in matching {
+            let map = builder()
+                .grid_size(NonZeroU16::new(40).unwrap())
+                .seed(&seed)
+                .build()
+                .generate_top_down_map()
+                .unwrap();
+
+            assert!(map.room_count() >= 5);
+            assert!(map.path_length().unwrap() > 20);
+        }
+    }
+}
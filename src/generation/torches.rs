@@ -0,0 +1,91 @@
+use std::num::NonZeroU16;
+
+use crate::map::{
+    cells::Coordinate,
+    layers::{FloorType, LayerType, StructureType},
+    Grid,
+};
+
+// Torches go on walls that face indoor floor, walking the candidates in
+// coordinate order and skipping any that are closer than `spacing` to a torch
+// already placed. No RNG is involved, so turning torches on leaves the rest
+// of the map alone.
+pub(crate) fn place_torches(grid: &mut Grid, spacing: NonZeroU16) -> Vec<Coordinate> {
+    let mut candidates: Vec<Coordinate> = grid
+        .cells()
+        .into_iter()
+        .filter(|cell| {
+            cell.visible_layer() == LayerType::Structure(StructureType::Wall)
+                && !grid.is_blocked(cell.coordinate())
+        })
+        .map(|cell| *cell.coordinate())
+        .filter(|coordinate| {
+            coordinate.neighbours().iter().any(|neighbour| {
+                grid.cell(neighbour).is_some_and(|cell| {
+                    cell.is_walkable() && cell.floor_type() == Some(FloorType::Indoor)
+                })
+            })
+        })
+        .collect();
+    candidates.sort();
+
+    let spacing = spacing.get() as u32;
+    let mut torches: Vec<Coordinate> = Vec::new();
+    for candidate in candidates {
+        if torches
+            .iter()
+            .all(|torch| torch.distance(&candidate) >= spacing)
+        {
+            grid.add_layer(&candidate, LayerType::Structure(StructureType::Torch));
+            torches.push(candidate);
+        }
+    }
+
+    torches
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::builder,
+        map::layers::{FloorType, LayerType, StructureType},
+    };
+
+    #[test]
+    fn torches_light_indoor_walls_without_moving_anything() {
+        let mut base = builder();
+        base.seed("torches").grid_size(NonZeroU16::new(40).unwrap());
+        let plain = base.build().generate_top_down_map().unwrap();
+
+        let mut lit = base.clone();
+        lit.torch_spacing(NonZeroU16::new(4).unwrap());
+        let map = lit.build().generate_top_down_map().unwrap();
+
+        assert_eq!(plain.entry(), map.entry());
+        assert_eq!(plain.exit(), map.exit());
+
+        let torches: Vec<_> = map
+            .grid()
+            .cells()
+            .into_iter()
+            .filter(|cell| cell.visible_layer() == LayerType::Structure(StructureType::Torch))
+            .collect();
+        assert!(!torches.is_empty());
+
+        for torch in torches.iter() {
+            assert!(torch
+                .layers()
+                .contains(&LayerType::Structure(StructureType::Wall)));
+            assert!(torch.coordinate().neighbours().iter().any(|neighbour| map
+                .grid()
+                .cell(neighbour)
+                .is_some_and(|cell| cell.floor_type() == Some(FloorType::Indoor))));
+
+            for other in torches.iter().filter(|other| other != &torch) {
+                assert!(torch.coordinate().distance(other.coordinate()) >= 4);
+            }
+        }
+    }
+}
@@ -1,3 +1,9 @@
+pub mod formats;
 pub mod generation;
 pub mod loading;
 pub mod map;
+pub mod render;
+pub mod world;
+
+#[cfg(test)]
+mod test_support;
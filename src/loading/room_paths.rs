@@ -1,17 +1,18 @@
 use std::fs;
 
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
-use crate::map::Room;
+use crate::{generation::GenerationError, map::Room};
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RoomPaths {
     pub name: String,
     pub template_path: String,
 }
 
 impl RoomPaths {
-    pub fn load_rooms(&self) -> Option<Vec<Room>> {
+    pub fn load_rooms(&self) -> Result<Vec<Room>, GenerationError> {
         let mut templates: Vec<Room> = Vec::new();
 
         for entry in WalkDir::new(self.template_path.as_str())
@@ -19,14 +20,16 @@ impl RoomPaths {
             .flatten()
         {
             if let Ok(content) = fs::read_to_string(entry.path()) {
-                templates.push(Room::from(&content));
+                templates.push(Room::from_template(&content).map_err(|reason| {
+                    GenerationError::invalid_room_template(&format!(
+                        "{}: {}",
+                        entry.path().display(),
+                        reason
+                    ))
+                })?);
             }
         }
 
-        if templates.is_empty() {
-            None
-        } else {
-            Some(templates)
-        }
+        Ok(templates)
     }
 }
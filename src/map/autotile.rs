@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use super::{
+    cells::{Cell, Coordinate},
+    layers::{LayerType, StructureType},
+    Grid,
+};
+
+pub const NORTH: u8 = 1;
+pub const NORTH_EAST: u8 = 2;
+pub const EAST: u8 = 4;
+pub const SOUTH_EAST: u8 = 8;
+pub const SOUTH: u8 = 16;
+pub const SOUTH_WEST: u8 = 32;
+pub const WEST: u8 = 64;
+pub const NORTH_WEST: u8 = 128;
+
+const DIRECTIONS: [(i32, i32, u8); 8] = [
+    (0, 1, NORTH),
+    (1, 1, NORTH_EAST),
+    (1, 0, EAST),
+    (1, -1, SOUTH_EAST),
+    (0, -1, SOUTH),
+    (-1, -1, SOUTH_WEST),
+    (-1, 0, WEST),
+    (-1, 1, NORTH_WEST),
+];
+
+// A corner only matters when both edges beside it are set, otherwise the
+// sprite for it looks the same as the one without it.
+pub fn reduce_blob(mask: u8) -> u8 {
+    let mut reduced = mask & (NORTH | EAST | SOUTH | WEST);
+
+    for (corner, first, second) in [
+        (NORTH_EAST, NORTH, EAST),
+        (SOUTH_EAST, SOUTH, EAST),
+        (SOUTH_WEST, SOUTH, WEST),
+        (NORTH_WEST, NORTH, WEST),
+    ] {
+        if mask & corner != 0 && mask & first != 0 && mask & second != 0 {
+            reduced |= corner;
+        }
+    }
+
+    reduced
+}
+
+// The 4 bit mask packs north, east, south and west into 0..16 for the
+// classic 16 tile set.
+pub fn cardinal_mask(mask: u8) -> u8 {
+    (mask & NORTH) | (mask & EAST) >> 1 | (mask & SOUTH) >> 2 | (mask & WEST) >> 3
+}
+
+// Position of the reduced mask among the 47 possible reduced masks, in
+// ascending order, for the 47 tile blob set.
+pub fn blob_index(mask: u8) -> u8 {
+    BLOB_INDICES[reduce_blob(mask) as usize]
+}
+
+const BLOB_INDICES: [u8; 256] = blob_indices();
+
+const fn blob_indices() -> [u8; 256] {
+    let mut indices = [0; 256];
+    let mut next = 0;
+    let mut mask = 0;
+
+    while mask < 256 {
+        if corner_allowed(mask as u8, NORTH_EAST, NORTH | EAST)
+            && corner_allowed(mask as u8, SOUTH_EAST, SOUTH | EAST)
+            && corner_allowed(mask as u8, SOUTH_WEST, SOUTH | WEST)
+            && corner_allowed(mask as u8, NORTH_WEST, NORTH | WEST)
+        {
+            indices[mask] = next;
+            next += 1;
+        }
+
+        mask += 1;
+    }
+
+    indices
+}
+
+const fn corner_allowed(mask: u8, corner: u8, edges: u8) -> bool {
+    mask & corner == 0 || mask & edges == edges
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AutotileMasks {
+    masks: HashMap<Coordinate, u8>,
+}
+
+// The predicate is checked against the topmost floor or structure of each
+// cell. Items, paths and torches sit on top of the tile rather than change
+// it, and anything buried is under it.
+fn terrain(cell: &Cell) -> Option<&LayerType> {
+    cell.layers().iter().rev().find(|layer| match layer {
+        LayerType::Structure(StructureType::Torch) => false,
+        LayerType::Floor(_) | LayerType::Structure(_) => true,
+        _ => false,
+    })
+}
+
+impl AutotileMasks {
+    pub fn build(grid: &Grid, predicate: impl Fn(&LayerType) -> bool) -> Self {
+        let matches = |coordinate: &Coordinate| {
+            grid.cell(coordinate)
+                .and_then(terrain)
+                .is_some_and(&predicate)
+        };
+
+        let masks = grid
+            .cells()
+            .into_iter()
+            .map(|cell| *cell.coordinate())
+            .filter(|coordinate| matches(coordinate))
+            .map(|coordinate| {
+                let mask = DIRECTIONS
+                    .iter()
+                    .filter(|(x, y, _)| {
+                        matches(&Coordinate::from((coordinate.x() + x, coordinate.y() + y)))
+                    })
+                    .fold(0, |mask, (_, _, bit)| mask | bit);
+                (coordinate, mask)
+            })
+            .collect();
+
+        Self { masks }
+    }
+
+    pub fn mask(&self, coordinate: &Coordinate) -> Option<u8> {
+        self.masks.get(coordinate).copied()
+    }
+
+    pub fn tile_16(&self, coordinate: &Coordinate) -> Option<u8> {
+        self.mask(coordinate).map(cardinal_mask)
+    }
+
+    pub fn tile_47(&self, coordinate: &Coordinate) -> Option<u8> {
+        self.mask(coordinate).map(blob_index)
+    }
+
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        let mut coordinates: Vec<Coordinate> = self.masks.keys().copied().collect();
+        coordinates.sort();
+        coordinates
+    }
+
+    pub fn len(&self) -> usize {
+        self.masks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::map::{
+        cells::Coordinate,
+        layers::{FloorType, ItemRarity, LayerType, StructureType},
+        Grid,
+    };
+
+    use super::{
+        blob_index, cardinal_mask, reduce_blob, AutotileMasks, EAST, NORTH, NORTH_EAST, NORTH_WEST,
+        SOUTH, SOUTH_EAST, SOUTH_WEST, WEST,
+    };
+
+    fn is_wall(layer: &LayerType) -> bool {
+        *layer == LayerType::Structure(StructureType::Wall)
+    }
+
+    #[test]
+    fn blob_reduces_to_47_tiles() {
+        let reduced: HashSet<u8> = (0..=255).map(reduce_blob).collect();
+        let indices: HashSet<u8> = (0..=255).map(blob_index).collect();
+
+        assert_eq!(reduced.len(), 47);
+        assert_eq!(indices, (0..47).collect());
+        assert_eq!(blob_index(0), 0);
+        assert_eq!(blob_index(255), 46);
+        assert_eq!(reduce_blob(NORTH | NORTH_EAST | SOUTH_EAST), NORTH);
+        assert_eq!(cardinal_mask(NORTH | EAST | SOUTH | WEST | NORTH_EAST), 15);
+    }
+
+    #[test]
+    fn masks_follow_neighbouring_walls() {
+        let mut grid = Grid::build(4, "autotile".to_string());
+        grid.fill_empty_cells();
+        for coordinate in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)] {
+            grid.add_layer(
+                &Coordinate::from(coordinate),
+                LayerType::Structure(StructureType::Wall),
+            );
+        }
+
+        let masks = AutotileMasks::build(&grid, is_wall);
+
+        assert_eq!(masks.len(), 5);
+        assert_eq!(masks.mask(&Coordinate::from((3, 3))), None);
+        assert_eq!(
+            masks.mask(&Coordinate::from((1, 0))),
+            Some(NORTH | NORTH_WEST | EAST | WEST)
+        );
+        assert_eq!(masks.tile_16(&Coordinate::from((1, 0))), Some(1 | 2 | 8));
+        assert_eq!(
+            masks.mask(&Coordinate::from((0, 0))),
+            Some(NORTH | NORTH_EAST | EAST)
+        );
+        assert_eq!(
+            masks.tile_47(&Coordinate::from((0, 0))),
+            Some(blob_index(NORTH | NORTH_EAST | EAST))
+        );
+        assert_eq!(
+            masks.mask(&Coordinate::from((2, 0))),
+            Some(WEST | NORTH_WEST)
+        );
+        assert_eq!(reduce_blob(WEST | NORTH_WEST), WEST);
+        assert_eq!(
+            masks.mask(&Coordinate::from((1, 1))),
+            Some(SOUTH | SOUTH_EAST | SOUTH_WEST | WEST)
+        );
+    }
+
+    #[test]
+    fn floor_masks_skip_walls_and_see_past_what_lies_on_top() {
+        let mut grid = Grid::build(3, "autotile".to_string());
+        grid.fill_empty_cells();
+        for y in 0..3 {
+            grid.replace_layer(
+                &Coordinate::from((0, y)),
+                &LayerType::Floor(FloorType::Outdoor),
+                LayerType::Floor(FloorType::Indoor),
+            );
+        }
+        grid.add_layer(
+            &Coordinate::from((1, 1)),
+            LayerType::Structure(StructureType::Wall),
+        );
+        grid.add_layer(
+            &Coordinate::from((1, 1)),
+            LayerType::Structure(StructureType::Torch),
+        );
+        grid.add_layer(&Coordinate::from((0, 1)), LayerType::Item(ItemRarity::Rare));
+        grid.bury_layer(&Coordinate::from((2, 2)), LayerType::Key);
+
+        let indoor =
+            AutotileMasks::build(&grid, |layer| *layer == LayerType::Floor(FloorType::Indoor));
+        assert_eq!(indoor.len(), 3);
+        assert_eq!(indoor.mask(&Coordinate::from((1, 1))), None);
+        assert_eq!(indoor.mask(&Coordinate::from((0, 1))), Some(NORTH | SOUTH));
+
+        let walls = AutotileMasks::build(&grid, is_wall);
+        assert_eq!(walls.len(), 1);
+        assert_eq!(walls.mask(&Coordinate::from((1, 1))), Some(0));
+
+        let keys = AutotileMasks::build(&grid, |layer| *layer == LayerType::Key);
+        assert!(keys.is_empty());
+    }
+}
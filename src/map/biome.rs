@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+// Not used yet, kept private until biomes are generated.
+#[allow(dead_code)]
 #[derive(PartialEq, Eq, Copy, Clone, Debug, Deserialize, Serialize)]
 pub enum Biome {}
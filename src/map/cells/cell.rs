@@ -28,6 +28,12 @@ impl From<(Coordinate, LayerType)> for Cell {
     }
 }
 
+impl From<(Coordinate, Vec<LayerType>)> for Cell {
+    fn from((coordinate, layers): (Coordinate, Vec<LayerType>)) -> Self {
+        Self { coordinate, layers }
+    }
+}
+
 impl From<(i32, i32)> for Cell {
     fn from(value: (i32, i32)) -> Self {
         Coordinate::from(value).into()
@@ -53,6 +59,12 @@ impl Cell {
         self.layers.retain(|l| l.ne(layer))
     }
 
+    pub fn replace_layer(&mut self, existing: &LayerType, replacement: LayerType) {
+        for layer in self.layers.iter_mut().filter(|layer| layer == &existing) {
+            *layer = replacement;
+        }
+    }
+
     pub fn clear_contents(&mut self) {
         self.layers.clear()
     }
@@ -102,10 +114,15 @@ impl Cell {
         self.cell_type_at_layer(0).unwrap()
     }
 
+    pub fn floor_type(&self) -> Option<FloorType> {
+        self.layers.iter().find_map(|layer| match layer {
+            LayerType::Floor(floor_type) => Some(*floor_type),
+            _ => None,
+        })
+    }
+
     pub fn contains_door(&self) -> bool {
-        self.layers
-            .iter()
-            .any(|layer| matches!(layer, LayerType::Structure(StructureType::Door)))
+        self.layers.iter().any(|layer| layer.is_door())
     }
 
     pub fn cell_type_at_layer(&self, layer_index: usize) -> Option<LayerType> {
@@ -138,10 +155,32 @@ impl Cell {
         }
     }
 
+    // Moves the highest buried layer to the top of the stack. This works by
+    // position rather than value, the same item can be both buried and lying
+    // on top.
+    pub fn reveal_buried_layer(&mut self) -> Option<LayerType> {
+        let burying = LayerType::can_bury_other_layers();
+        let surface = self
+            .layers
+            .iter()
+            .rposition(|layer| burying.contains(layer))?;
+        let index = self.layers[..surface]
+            .iter()
+            .rposition(|layer| !burying.contains(layer))?;
+
+        let layer = self.layers.remove(index);
+        self.layers.push(layer);
+        Some(layer)
+    }
+
     pub fn is_walkable(&self) -> bool {
         self.visible_layer().is_walkable()
     }
 
+    pub fn movement_cost(&self) -> u32 {
+        self.visible_layer().movement_cost()
+    }
+
     pub fn is_spawnable(&self) -> bool {
         if self.layers.is_empty() {
             false
@@ -160,6 +199,10 @@ impl Cell {
         }
     }
 
+    pub fn is_opaque(&self) -> bool {
+        self.layers.iter().any(|layer| layer.is_opaque())
+    }
+
     pub fn translate(&self, x: i32, y: i32) -> Self {
         Self {
             coordinate: Coordinate::from((x, y)),
@@ -172,9 +215,40 @@ impl Cell {
 mod tests {
     use crate::map::{
         cells::{Cell, Coordinate},
-        layers::{FloorType, LayerType, StructureType},
+        layers::{FloorType, ItemRarity, LayerType, StructureType},
     };
 
+    #[test]
+    fn reveal_buried_layer() {
+        let mut cell = Cell::from(2);
+        cell.add_layer(LayerType::Floor(FloorType::Outdoor));
+        cell.bury_layer(&LayerType::Item(ItemRarity::Rare));
+        cell.bury_layer(&LayerType::Key);
+
+        assert_eq!(
+            cell.reveal_buried_layer(),
+            Some(LayerType::Item(ItemRarity::Rare))
+        );
+        assert_eq!(cell.visible_layer(), LayerType::Item(ItemRarity::Rare));
+        assert_eq!(cell.reveal_buried_layer(), Some(LayerType::Key));
+        assert_eq!(cell.reveal_buried_layer(), None);
+
+        let mut cell = Cell::from((
+            Coordinate::from(2),
+            vec![
+                LayerType::Item(ItemRarity::Rare),
+                LayerType::Floor(FloorType::Outdoor),
+                LayerType::Item(ItemRarity::Rare),
+            ],
+        ));
+        assert_eq!(
+            cell.reveal_buried_layer(),
+            Some(LayerType::Item(ItemRarity::Rare))
+        );
+        assert_eq!(cell.reveal_buried_layer(), None);
+        assert_eq!(cell.layers()[0], LayerType::Floor(FloorType::Outdoor));
+    }
+
     #[test]
     fn remove_layer() {
         let mut cell = Cell {
@@ -2,7 +2,7 @@ use std::hash::Hash;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Eq, Hash, PartialEq, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Eq, Hash, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
 pub struct Coordinate {
     x: i32,
     y: i32,
@@ -25,6 +25,15 @@ impl Coordinate {
         self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
     }
 
+    pub fn neighbours(&self) -> [Coordinate; 4] {
+        [
+            Coordinate::from((self.x - 1, self.y)),
+            Coordinate::from((self.x, self.y + 1)),
+            Coordinate::from((self.x + 1, self.y)),
+            Coordinate::from((self.x, self.y - 1)),
+        ]
+    }
+
     pub fn x(&self) -> i32 {
         self.x
     }
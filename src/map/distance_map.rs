@@ -0,0 +1,212 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    num::NonZeroU32,
+    ops::RangeInclusive,
+};
+
+use super::{cells::Coordinate, Grid};
+
+#[derive(Clone, Debug, Default)]
+pub struct DistanceMap {
+    distances: HashMap<Coordinate, u32>,
+}
+
+impl DistanceMap {
+    pub fn build(grid: &Grid, sources: &[Coordinate]) -> Self {
+        let mut distances: HashMap<Coordinate, u32> = HashMap::new();
+        let mut frontier: BinaryHeap<Reverse<(u32, Coordinate)>> = BinaryHeap::new();
+
+        for source in sources.iter().filter(|source| grid.cell(source).is_some()) {
+            distances.insert(*source, 0);
+            frontier.push(Reverse((0, *source)));
+        }
+
+        while let Some(Reverse((distance, coordinate))) = frontier.pop() {
+            if distances
+                .get(&coordinate)
+                .is_some_and(|existing| *existing < distance)
+            {
+                continue;
+            }
+
+            for neighbour in grid.surrounding_walkable_coordinates(&coordinate) {
+                let cost = grid
+                    .cell(&neighbour)
+                    .map(|cell| cell.movement_cost())
+                    .unwrap_or(1);
+                let next_distance = distance + cost;
+
+                if distances
+                    .get(&neighbour)
+                    .is_none_or(|existing| next_distance < *existing)
+                {
+                    distances.insert(neighbour, next_distance);
+                    frontier.push(Reverse((next_distance, neighbour)));
+                }
+            }
+        }
+
+        Self { distances }
+    }
+
+    pub fn value(&self, coordinate: &Coordinate) -> Option<u32> {
+        self.distances.get(coordinate).copied()
+    }
+
+    pub fn is_reachable(&self, coordinate: &Coordinate) -> bool {
+        self.distances.contains_key(coordinate)
+    }
+
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    pub fn downhill(&self, coordinate: &Coordinate) -> Option<Coordinate> {
+        let current = self.value(coordinate)?;
+
+        coordinate
+            .neighbours()
+            .into_iter()
+            .filter_map(|neighbour| self.value(&neighbour).map(|value| (value, neighbour)))
+            .filter(|(value, _)| *value < current)
+            .min()
+            .map(|(_, neighbour)| neighbour)
+    }
+
+    pub fn uphill(&self, coordinate: &Coordinate) -> Option<Coordinate> {
+        let current = self.value(coordinate)?;
+
+        coordinate
+            .neighbours()
+            .into_iter()
+            .filter_map(|neighbour| self.value(&neighbour).map(|value| (value, neighbour)))
+            .filter(|(value, _)| *value > current)
+            .max_by_key(|(value, neighbour)| (*value, Reverse(*neighbour)))
+            .map(|(_, neighbour)| neighbour)
+    }
+
+    pub fn farthest(&self) -> Option<(Coordinate, u32)> {
+        self.distances
+            .iter()
+            .max_by_key(|(coordinate, value)| (**value, Reverse(**coordinate)))
+            .map(|(coordinate, value)| (*coordinate, *value))
+    }
+
+    pub fn band(&self, range: RangeInclusive<u32>) -> Vec<Coordinate> {
+        let mut coordinates: Vec<Coordinate> = self
+            .distances
+            .iter()
+            .filter(|(_, value)| range.contains(value))
+            .map(|(coordinate, _)| *coordinate)
+            .collect();
+        coordinates.sort();
+        coordinates
+    }
+
+    pub fn bands(&self, band_width: NonZeroU32) -> Vec<Vec<Coordinate>> {
+        let max = match self.distances.values().max() {
+            Some(it) => *it,
+            None => return Vec::new(),
+        };
+
+        (0..=max / band_width.get())
+            .map(|index| {
+                let start = index * band_width.get();
+                self.band(start..=(start + band_width.get() - 1))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use crate::{
+        map::{
+            cells::Coordinate,
+            layers::{LayerType, StructureType},
+        },
+        test_support::open_grid,
+    };
+
+    use super::DistanceMap;
+
+    #[test]
+    fn distances_grow_from_source() {
+        let grid = open_grid(5);
+        let map = DistanceMap::build(&grid, &[Coordinate::from(0)]);
+
+        assert_eq!(map.value(&Coordinate::from(0)), Some(0));
+        assert_eq!(map.value(&Coordinate::from((3, 0))), Some(3));
+        assert_eq!(map.value(&Coordinate::from(4)), Some(8));
+        assert_eq!(map.farthest(), Some((Coordinate::from(4), 8)));
+    }
+
+    #[test]
+    fn multiple_sources_take_the_closest() {
+        let grid = open_grid(5);
+        let map = DistanceMap::build(&grid, &[Coordinate::from(0), Coordinate::from(4)]);
+
+        assert_eq!(map.value(&Coordinate::from((4, 0))), Some(4));
+        assert_eq!(map.value(&Coordinate::from((3, 4))), Some(1));
+    }
+
+    #[test]
+    fn walls_are_not_reachable_and_rubble_costs_more() {
+        let mut grid = open_grid(3);
+        grid.add_layer(
+            &Coordinate::from((1, 0)),
+            LayerType::Structure(StructureType::Wall),
+        );
+        grid.add_layer(
+            &Coordinate::from((1, 1)),
+            LayerType::Structure(StructureType::Rubble),
+        );
+        let map = DistanceMap::build(&grid, &[Coordinate::from(0)]);
+
+        assert!(!map.is_reachable(&Coordinate::from((1, 0))));
+        assert_eq!(map.value(&Coordinate::from((1, 1))), Some(4));
+        assert_eq!(map.value(&Coordinate::from((2, 0))), Some(6));
+    }
+
+    #[test]
+    fn downhill_leads_back_to_source() {
+        let grid = open_grid(4);
+        let map = DistanceMap::build(&grid, &[Coordinate::from(0)]);
+        let mut current = Coordinate::from(3);
+        let mut steps = 0;
+
+        while let Some(next) = map.downhill(&current) {
+            current = next;
+            steps += 1;
+        }
+
+        assert_eq!(current, Coordinate::from(0));
+        assert_eq!(steps, 6);
+        assert!(map.uphill(&Coordinate::from(3)).is_none());
+    }
+
+    #[test]
+    fn bands_group_by_distance() {
+        let grid = open_grid(3);
+        let map = DistanceMap::build(&grid, &[Coordinate::from(0)]);
+        let bands = map.bands(NonZeroU32::new(2).unwrap());
+
+        assert_eq!(bands.len(), 3);
+        assert_eq!(
+            bands[0],
+            vec![
+                Coordinate::from(0),
+                Coordinate::from((0, 1)),
+                Coordinate::from((1, 0))
+            ]
+        );
+        assert_eq!(bands[2], vec![Coordinate::from(2)]);
+    }
+}
@@ -0,0 +1,161 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DoorPolicy {
+    pub door_count: RangeInclusive<u8>,
+    pub prefer_different_sides: bool,
+    pub face_nearest_room: bool,
+    pub keep_template_doors: bool,
+    pub open_chance: u8,
+    pub locked_chance: u8,
+    pub secret_chance: u8,
+}
+
+impl Default for DoorPolicy {
+    fn default() -> Self {
+        Self {
+            door_count: 1..=1,
+            prefer_different_sides: false,
+            face_nearest_room: false,
+            keep_template_doors: false,
+            open_chance: 0,
+            locked_chance: 0,
+            secret_chance: 0,
+        }
+    }
+}
+
+impl DoorPolicy {
+    // Template files can start with `@key = value` lines that give the room
+    // its own policy, e.g. `@door_count = 2..=3` or `@secret_chance = 20`.
+    // Keys left out keep their default.
+    pub fn from_template_header(template: &str) -> Result<Option<Self>, String> {
+        let mut policy: Option<DoorPolicy> = None;
+
+        for line in template
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .take_while(|line| line.starts_with('@'))
+        {
+            let (key, value) = line[1..]
+                .split_once('=')
+                .ok_or_else(|| format!("{} is not written as @key = value", line))?;
+            let (key, value) = (key.trim(), value.trim());
+            let policy = policy.get_or_insert_with(DoorPolicy::default);
+
+            match key {
+                "door_count" => policy.door_count = parse_count(value)?,
+                "prefer_different_sides" => policy.prefer_different_sides = parse(key, value)?,
+                "face_nearest_room" => policy.face_nearest_room = parse(key, value)?,
+                "keep_template_doors" => policy.keep_template_doors = parse(key, value)?,
+                "open_chance" => policy.open_chance = parse(key, value)?,
+                "locked_chance" => policy.locked_chance = parse(key, value)?,
+                "secret_chance" => policy.secret_chance = parse(key, value)?,
+                _ => return Err(format!("unknown door policy key {}", key)),
+            }
+        }
+
+        if let Some(policy) = &policy {
+            if policy.door_count.start() > policy.door_count.end() {
+                return Err("door_count must not be an empty range".to_string());
+            }
+
+            let chances = policy.open_chance as u16
+                + policy.locked_chance as u16
+                + policy.secret_chance as u16;
+            if chances > 100 {
+                return Err(format!(
+                    "open, locked and secret chances add up to {}, more than 100",
+                    chances
+                ));
+            }
+        }
+
+        Ok(policy)
+    }
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} is not a valid value for {}", value, key))
+}
+
+fn parse_count(value: &str) -> Result<RangeInclusive<u8>, String> {
+    match value.split_once("..=") {
+        Some((start, end)) => {
+            Ok(parse("door_count", start.trim())?..=parse("door_count", end.trim())?)
+        }
+        None => {
+            let count = parse("door_count", value)?;
+            Ok(count..=count)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DoorPolicy;
+
+    #[test]
+    fn template_headers_set_the_policy() {
+        let policy = DoorPolicy::from_template_header(
+            "@door_count = 2..=3\n@ secret_chance = 25\n@face_nearest_room=true\n||||\n|==|\n||||",
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            policy,
+            DoorPolicy {
+                door_count: 2..=3,
+                face_nearest_room: true,
+                secret_chance: 25,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            DoorPolicy::from_template_header("@door_count = 4")
+                .unwrap()
+                .unwrap()
+                .door_count,
+            4..=4
+        );
+        assert_eq!(
+            DoorPolicy::from_template_header("||||\n|==|\n||||"),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn bad_headers_explain_themselves() {
+        for (header, error) in [
+            (
+                "@door_count 2",
+                "@door_count 2 is not written as @key = value",
+            ),
+            ("@doors = 2", "unknown door policy key doors"),
+            (
+                "@open_chance = lots",
+                "lots is not a valid value for open_chance",
+            ),
+            (
+                "@door_count = 3..=1",
+                "door_count must not be an empty range",
+            ),
+            (
+                "@open_chance = 60\n@locked_chance = 60",
+                "open, locked and secret chances add up to 120, more than 100",
+            ),
+        ] {
+            assert_eq!(
+                DoorPolicy::from_template_header(header),
+                Err(error.to_string())
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use super::{cells::Coordinate, FieldOfView, TopDownMap};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    #[default]
+    Unseen,
+    Seen,
+    Visible,
+}
+
+impl Visibility {
+    fn code(&self) -> char {
+        match self {
+            Visibility::Unseen => 'u',
+            Visibility::Seen => 's',
+            Visibility::Visible => 'v',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            'u' => Some(Visibility::Unseen),
+            's' => Some(Visibility::Seen),
+            'v' => Some(Visibility::Visible),
+            _ => None,
+        }
+    }
+}
+
+// States are stored row by row from the map's bounds, so the overlay only
+// makes sense for the map it was created from.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "ExplorationRecord", into = "ExplorationRecord")]
+pub struct Exploration {
+    min: Coordinate,
+    width: usize,
+    height: usize,
+    states: Vec<Visibility>,
+}
+
+impl Exploration {
+    pub fn new(map: &TopDownMap) -> Self {
+        let (min, max) = map.grid().bounds();
+        let width = (max.x() - min.x() + 1) as usize;
+        let height = (max.y() - min.y() + 1) as usize;
+
+        Self {
+            min,
+            width,
+            height,
+            states: vec![Visibility::Unseen; width * height],
+        }
+    }
+
+    pub fn state(&self, coordinate: &Coordinate) -> Visibility {
+        self.index(coordinate)
+            .map(|index| self.states[index])
+            .unwrap_or_default()
+    }
+
+    pub fn is_explored(&self, coordinate: &Coordinate) -> bool {
+        self.state(coordinate) != Visibility::Unseen
+    }
+
+    // Whatever was visible drops back to seen before the new view is applied.
+    pub fn update(&mut self, field_of_view: &FieldOfView) {
+        for state in self
+            .states
+            .iter_mut()
+            .filter(|state| **state == Visibility::Visible)
+        {
+            *state = Visibility::Seen;
+        }
+
+        for coordinate in field_of_view.visible() {
+            if let Some(index) = self.index(coordinate) {
+                self.states[index] = Visibility::Visible;
+            }
+        }
+    }
+
+    pub fn mark_seen(&mut self, coordinate: &Coordinate) {
+        if let Some(index) = self.index(coordinate) {
+            if self.states[index] == Visibility::Unseen {
+                self.states[index] = Visibility::Seen;
+            }
+        }
+    }
+
+    pub fn explored_count(&self) -> usize {
+        self.states
+            .iter()
+            .filter(|state| **state != Visibility::Unseen)
+            .count()
+    }
+
+    // Same layout as `Grid::top_layer_display`, with unexplored cells left
+    // blank.
+    pub fn remembered_display(&self, map: &TopDownMap) -> String {
+        let mut text = String::new();
+        let size = map.grid().size() as i32;
+
+        for y in (-1..=size).rev() {
+            for x in -1..=size {
+                let coordinate = Coordinate::from((x, y));
+                if self.is_explored(&coordinate) {
+                    let layer_type = map
+                        .grid()
+                        .cell(&coordinate)
+                        .map(|cell| cell.visible_layer())
+                        .unwrap_or_default();
+                    text.push(layer_type.into());
+                } else {
+                    text.push(' ');
+                }
+            }
+
+            text.push('\n');
+        }
+
+        text
+    }
+
+    fn index(&self, coordinate: &Coordinate) -> Option<usize> {
+        let x = usize::try_from(coordinate.x() - self.min.x()).ok()?;
+        let y = usize::try_from(coordinate.y() - self.min.y()).ok()?;
+
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+}
+
+// Explored areas tend to be big connected blobs, so the states are run length
+// encoded as count and state code pairs, e.g. "120u14s9v".
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct ExplorationRecord {
+    min: Coordinate,
+    width: usize,
+    height: usize,
+    states: String,
+}
+
+impl From<Exploration> for ExplorationRecord {
+    fn from(exploration: Exploration) -> Self {
+        let mut states = String::new();
+        for run in exploration.states.chunk_by(|a, b| a == b) {
+            states.push_str(&run.len().to_string());
+            states.push(run[0].code());
+        }
+
+        Self {
+            min: exploration.min,
+            width: exploration.width,
+            height: exploration.height,
+            states,
+        }
+    }
+}
+
+impl TryFrom<ExplorationRecord> for Exploration {
+    type Error = String;
+
+    fn try_from(record: ExplorationRecord) -> Result<Self, Self::Error> {
+        let expected = record.width.checked_mul(record.height).ok_or_else(|| {
+            format!(
+                "{}x{} is too large for an exploration overlay",
+                record.width, record.height
+            )
+        })?;
+        // The sizes come from the file, so the runs are checked against them
+        // before anything is allocated.
+        let mut states: Vec<Visibility> = Vec::new();
+        let mut count = String::new();
+
+        for character in record.states.chars() {
+            if character.is_ascii_digit() {
+                count.push(character);
+                continue;
+            }
+
+            let state = Visibility::from_code(character)
+                .ok_or_else(|| format!("unknown visibility state {}", character))?;
+            let run: usize = count
+                .parse()
+                .map_err(|_| format!("missing run length before {}", character))?;
+            if run > expected - states.len() {
+                return Err(format!(
+                    "expected {} visibility states, found more",
+                    expected
+                ));
+            }
+            states.extend(std::iter::repeat_n(state, run));
+            count.clear();
+        }
+
+        if !count.is_empty() || states.len() != expected {
+            return Err(format!(
+                "expected {} visibility states, found {}",
+                expected,
+                states.len()
+            ));
+        }
+
+        Ok(Self {
+            min: record.min,
+            width: record.width,
+            height: record.height,
+            states,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        map::{cells::Coordinate, FieldOfView},
+        test_support::map,
+    };
+
+    use super::{Exploration, Visibility};
+
+    #[test]
+    fn visible_cells_fall_back_to_seen() {
+        let map = map("exploration", 30);
+        let mut exploration = Exploration::new(&map);
+        let entry = *map.entry();
+        let exit = *map.exit();
+
+        assert_eq!(exploration.state(&entry), Visibility::Unseen);
+        assert_eq!(exploration.explored_count(), 0);
+
+        let first = map.grid().field_of_view(&entry, 6);
+        exploration.update(&first);
+        assert_eq!(exploration.state(&entry), Visibility::Visible);
+        assert_eq!(exploration.explored_count(), first.len());
+
+        exploration.update(&FieldOfView::default());
+        assert_eq!(exploration.state(&entry), Visibility::Seen);
+        assert_eq!(exploration.explored_count(), first.len());
+
+        exploration.update(&map.grid().field_of_view(&exit, 6));
+        assert_eq!(exploration.state(&exit), Visibility::Visible);
+        assert_eq!(
+            exploration.state(&Coordinate::from((500, 500))),
+            Visibility::Unseen
+        );
+    }
+
+    #[test]
+    fn remembered_display_hides_unexplored_cells() {
+        let map = map("exploration", 30);
+        let mut exploration = Exploration::new(&map);
+        exploration.update(&map.grid().field_of_view(map.entry(), 5));
+
+        let full = map.grid().top_layer_display();
+        let remembered = exploration.remembered_display(&map);
+
+        assert_eq!(full.lines().count(), remembered.lines().count());
+        for (full_character, remembered_character) in full.chars().zip(remembered.chars()) {
+            assert!(remembered_character == ' ' || remembered_character == full_character);
+        }
+        assert_eq!(
+            remembered.chars().filter(|c| !c.is_whitespace()).count(),
+            exploration.explored_count()
+        );
+    }
+
+    #[test]
+    fn serializes_as_runs() {
+        let map = map("exploration", 30);
+        let mut exploration = Exploration::new(&map);
+        exploration.update(&map.grid().field_of_view(map.entry(), 4));
+        exploration.update(&map.grid().field_of_view(map.exit(), 4));
+
+        let serialized = serde_json::to_string(&exploration).unwrap();
+        assert!(serialized.len() < 32 * 32);
+
+        let deserialized: Exploration = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, exploration);
+
+        let empty = serde_json::to_value(Exploration::new(&map)).unwrap();
+        assert_eq!(empty["states"], "1024u");
+        for invalid in [
+            r#"{"min":{"x":0,"y":0},"width":2,"height":2,"states":"3u"}"#,
+            r#"{"min":{"x":0,"y":0},"width":2,"height":2,"states":"3u99999999999999s"}"#,
+            r#"{"min":{"x":0,"y":0},"width":18446744073709551615,"height":2,"states":"1u"}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<Exploration>(invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use super::{cells::Coordinate, Grid};
+
+#[derive(Clone, Debug, Default)]
+pub struct FieldOfView {
+    visible: HashSet<Coordinate>,
+}
+
+impl FieldOfView {
+    // Symmetric shadowcasting, so if a can see b then b can see a.
+    pub fn build(grid: &Grid, origin: &Coordinate, radius: u32) -> Self {
+        let mut visible: HashSet<Coordinate> = HashSet::new();
+        if grid.cell(origin).is_none() {
+            return Self { visible };
+        }
+        visible.insert(*origin);
+
+        let radius = radius as i64;
+        let is_opaque =
+            |coordinate: &Coordinate| grid.cell(coordinate).is_none_or(|cell| cell.is_opaque());
+
+        for quadrant in [
+            Quadrant::North,
+            Quadrant::East,
+            Quadrant::South,
+            Quadrant::West,
+        ] {
+            let mut rows = vec![Row {
+                depth: 1,
+                start: Slope::new(-1, 1),
+                end: Slope::new(1, 1),
+            }];
+
+            while let Some(mut row) = rows.pop() {
+                if row.depth > radius {
+                    continue;
+                }
+
+                let mut previous_opaque: Option<bool> = None;
+                for column in row.min_column()..=row.max_column() {
+                    let coordinate = quadrant.transform(origin, row.depth, column);
+                    let opaque = is_opaque(&coordinate);
+                    let in_radius = row.depth * row.depth + column * column <= radius * radius;
+
+                    if in_radius && (opaque || row.is_symmetric(column)) {
+                        visible.insert(coordinate);
+                    }
+
+                    if previous_opaque == Some(true) && !opaque {
+                        row.start = Slope::of(row.depth, column);
+                    }
+
+                    if previous_opaque == Some(false) && opaque {
+                        rows.push(Row {
+                            depth: row.depth + 1,
+                            start: row.start,
+                            end: Slope::of(row.depth, column),
+                        });
+                    }
+
+                    previous_opaque = Some(opaque);
+                }
+
+                if previous_opaque == Some(false) {
+                    rows.push(Row {
+                        depth: row.depth + 1,
+                        ..row
+                    });
+                }
+            }
+        }
+
+        // Anything without a cell was only treated as opaque to stop the scan.
+        visible.retain(|coordinate| grid.cell(coordinate).is_some());
+
+        Self { visible }
+    }
+
+    pub fn is_visible(&self, coordinate: &Coordinate) -> bool {
+        self.visible.contains(coordinate)
+    }
+
+    pub fn visible(&self) -> &HashSet<Coordinate> {
+        &self.visible
+    }
+
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        let mut coordinates: Vec<Coordinate> = self.visible.iter().copied().collect();
+        coordinates.sort();
+        coordinates
+    }
+
+    pub fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.visible.is_empty()
+    }
+}
+
+pub fn line(from: &Coordinate, to: &Coordinate) -> Vec<Coordinate> {
+    let dx = (to.x() - from.x()).abs();
+    let dy = -(to.y() - from.y()).abs();
+    let step_x = if from.x() < to.x() { 1 } else { -1 };
+    let step_y = if from.y() < to.y() { 1 } else { -1 };
+
+    let mut line = Vec::new();
+    let (mut x, mut y) = (from.x(), from.y());
+    let mut error = dx + dy;
+
+    loop {
+        line.push(Coordinate::from((x, y)));
+        if x == to.x() && y == to.y() {
+            return line;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+// Only the cells between the two ends need to be clear, so a wall can still be
+// seen from the floor in front of it.
+pub fn line_of_sight(grid: &Grid, from: &Coordinate, to: &Coordinate) -> bool {
+    let line = line(from, to);
+
+    line.iter()
+        .skip(1)
+        .take(line.len().saturating_sub(2))
+        .all(|coordinate| grid.cell(coordinate).is_some_and(|cell| !cell.is_opaque()))
+}
+
+#[derive(Copy, Clone)]
+enum Quadrant {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Quadrant {
+    fn transform(&self, origin: &Coordinate, depth: i64, column: i64) -> Coordinate {
+        let (depth, column) = (depth as i32, column as i32);
+        let (x, y) = match self {
+            Quadrant::North => (origin.x() + column, origin.y() + depth),
+            Quadrant::South => (origin.x() + column, origin.y() - depth),
+            Quadrant::East => (origin.x() + depth, origin.y() + column),
+            Quadrant::West => (origin.x() - depth, origin.y() + column),
+        };
+        Coordinate::from((x, y))
+    }
+}
+
+// Slopes are kept as exact fractions so rounding never lets light leak
+// through the corner of a wall.
+#[derive(Copy, Clone, Debug)]
+struct Slope {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Slope {
+    fn new(numerator: i64, denominator: i64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    fn of(depth: i64, column: i64) -> Self {
+        Self::new(2 * column - 1, 2 * depth)
+    }
+
+    fn compare(&self, depth: i64, column: i64) -> Ordering {
+        (column * self.denominator).cmp(&(depth * self.numerator))
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Row {
+    depth: i64,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    // depth * start rounded with ties going up.
+    fn min_column(&self) -> i64 {
+        (2 * self.depth * self.start.numerator + self.start.denominator)
+            .div_euclid(2 * self.start.denominator)
+    }
+
+    // depth * end rounded with ties going down.
+    fn max_column(&self) -> i64 {
+        -(-(2 * self.depth * self.end.numerator - self.end.denominator))
+            .div_euclid(2 * self.end.denominator)
+    }
+
+    fn is_symmetric(&self, column: i64) -> bool {
+        self.start.compare(self.depth, column) != Ordering::Less
+            && self.end.compare(self.depth, column) != Ordering::Greater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::builder,
+        map::{
+            cells::Coordinate,
+            layers::{LayerType, StructureType},
+        },
+        test_support::open_grid,
+    };
+
+    use super::{line, line_of_sight, FieldOfView};
+
+    #[test]
+    fn open_ground_is_visible_within_radius() {
+        let grid = open_grid(11);
+        let fov = FieldOfView::build(&grid, &Coordinate::from(5), 3);
+
+        let expected = (0..11)
+            .flat_map(|x| (0..11).map(move |y| (x - 5, y - 5)))
+            .filter(|(x, y)| x * x + y * y <= 9)
+            .count();
+        assert_eq!(fov.len(), expected);
+        assert!(fov.is_visible(&Coordinate::from((5, 8))));
+        assert!(!fov.is_visible(&Coordinate::from((8, 8))));
+    }
+
+    #[test]
+    fn walls_cast_shadows() {
+        let mut grid = open_grid(9);
+        for y in 0..9 {
+            grid.add_layer(
+                &Coordinate::from((5, y)),
+                LayerType::Structure(StructureType::Wall),
+            );
+        }
+        grid.replace_layer(
+            &Coordinate::from((5, 6)),
+            &LayerType::Structure(StructureType::Wall),
+            LayerType::Structure(StructureType::OpenDoor),
+        );
+
+        let origin = Coordinate::from((2, 4));
+        let fov = FieldOfView::build(&grid, &origin, 10);
+
+        assert!(fov.is_visible(&Coordinate::from((5, 4))));
+        assert!(!fov.is_visible(&Coordinate::from((6, 4))));
+        assert!(fov.is_visible(&Coordinate::from((6, 6))));
+        assert!(line_of_sight(&grid, &origin, &Coordinate::from((5, 4))));
+        assert!(!line_of_sight(&grid, &origin, &Coordinate::from((7, 4))));
+    }
+
+    #[test]
+    fn visibility_is_symmetric() {
+        let map = builder()
+            .seed("field of view")
+            .grid_size(NonZeroU16::new(30).unwrap())
+            .build()
+            .generate_top_down_map()
+            .unwrap();
+        let grid = map.grid();
+        let seen: Vec<Coordinate> = grid
+            .cells()
+            .into_iter()
+            .filter(|cell| !cell.is_opaque())
+            .map(|cell| *cell.coordinate())
+            .filter(|coordinate| coordinate.x() % 4 == 0 && coordinate.y() % 4 == 0)
+            .collect();
+
+        for from in seen.iter() {
+            let fov = grid.field_of_view(from, 12);
+            for to in seen.iter().filter(|to| fov.is_visible(to)) {
+                assert!(grid.field_of_view(to, 12).is_visible(from));
+            }
+        }
+    }
+
+    #[test]
+    fn lines_include_both_ends() {
+        assert_eq!(
+            line(&Coordinate::from((0, 0)), &Coordinate::from((3, 1))),
+            vec![
+                Coordinate::from((0, 0)),
+                Coordinate::from((1, 0)),
+                Coordinate::from((2, 1)),
+                Coordinate::from((3, 1)),
+            ]
+        );
+        assert_eq!(
+            line(&Coordinate::from(2), &Coordinate::from(2)),
+            vec![Coordinate::from(2)]
+        );
+    }
+}
@@ -1,16 +1,21 @@
 use super::{
+    autotile::AutotileMasks,
     cells::{Cell, Coordinate},
-    layers::{LayerType, StructureType},
-    Room,
+    field_of_view::{self, FieldOfView},
+    layers::{FloorType, LayerType, StructureType},
+    DistanceMap, GridDiff, GridEvent, Region, Room,
 };
 use rand::prelude::*;
 use rand_pcg::Pcg64;
 use rand_seeder::Seeder;
-use std::collections::HashMap;
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct Grid {
     cells: HashMap<Coordinate, Cell>,
+    rooms: Vec<Room>,
+    blocked: HashSet<Coordinate>,
     size: u16,
     rng: Pcg64,
 }
@@ -24,6 +29,48 @@ impl Grid {
         self.cells.values().collect()
     }
 
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    pub fn bounds(&self) -> (Coordinate, Coordinate) {
+        let min_x = self.cells.keys().map(|coordinate| coordinate.x()).min();
+        let min_y = self.cells.keys().map(|coordinate| coordinate.y()).min();
+        let max_x = self.cells.keys().map(|coordinate| coordinate.x()).max();
+        let max_y = self.cells.keys().map(|coordinate| coordinate.y()).max();
+
+        (
+            Coordinate::from((min_x.unwrap_or_default(), min_y.unwrap_or_default())),
+            Coordinate::from((max_x.unwrap_or_default(), max_y.unwrap_or_default())),
+        )
+    }
+
+    pub fn rooms(&self) -> &Vec<Room> {
+        &self.rooms
+    }
+
+    pub fn room_index_at(&self, coordinate: &Coordinate) -> Option<usize> {
+        self.rooms
+            .iter()
+            .position(|room| room.cell_at(coordinate.x(), coordinate.y()).is_some())
+    }
+
+    pub fn distance_map(&self, sources: &[Coordinate]) -> DistanceMap {
+        DistanceMap::build(self, sources)
+    }
+
+    pub fn field_of_view(&self, origin: &Coordinate, radius: u32) -> FieldOfView {
+        FieldOfView::build(self, origin, radius)
+    }
+
+    pub fn line_of_sight(&self, from: &Coordinate, to: &Coordinate) -> bool {
+        field_of_view::line_of_sight(self, from, to)
+    }
+
+    pub fn autotile_masks(&self, predicate: impl Fn(&LayerType) -> bool) -> AutotileMasks {
+        AutotileMasks::build(self, predicate)
+    }
+
     pub fn top_layer_display(&self) -> String {
         let mut text = String::new();
 
@@ -44,22 +91,13 @@ impl Grid {
         text
     }
 
-    fn add_cell(&mut self, cell: Cell) {
+    pub(crate) fn add_cell(&mut self, cell: Cell) {
         self.cells.insert(*cell.coordinate(), cell);
     }
 
     pub fn surrounding_walkable_coordinates(&self, coordinate: &Coordinate) -> Vec<Coordinate> {
-        let surrounding: Vec<Coordinate> = vec![
-            // Coordinate::new(coordinate.x - 1, coordinate.y - 1),
-            Coordinate::from((coordinate.x() - 1, coordinate.y())),
-            // Coordinate::new(coordinate.x - 1, coordinate.y + 1),
-            Coordinate::from((coordinate.x(), coordinate.y() + 1)),
-            // Coordinate::new(coordinate.x + 1, coordinate.y + 1),
-            Coordinate::from((coordinate.x() + 1, coordinate.y())),
-            // Coordinate::new(coordinate.x + 1, coordinate.y - 1),
-            Coordinate::from((coordinate.x(), coordinate.y() - 1)),
-        ];
-        surrounding
+        coordinate
+            .neighbours()
             .iter()
             .filter_map(|c| self.cells.get(c).filter(|cell| cell.is_walkable()))
             .map(|cell| cell.coordinate())
@@ -67,6 +105,205 @@ impl Grid {
             .collect()
     }
 
+    pub fn is_in_bounds(&self, coordinate: &Coordinate) -> bool {
+        (0..self.size as i32).contains(&coordinate.x())
+            && (0..self.size as i32).contains(&coordinate.y())
+    }
+
+    pub fn regions(&self) -> Vec<Region> {
+        let mut walkable: Vec<Coordinate> = self
+            .cells
+            .values()
+            .filter(|cell| cell.is_walkable())
+            .map(|cell| *cell.coordinate())
+            .collect();
+        walkable.sort();
+
+        let mut visited: HashSet<Coordinate> = HashSet::new();
+        let mut regions: Vec<Region> = Vec::new();
+
+        for start in walkable {
+            if !visited.insert(start) {
+                continue;
+            }
+
+            let mut coordinates = vec![start];
+            let mut frontier = vec![start];
+
+            while let Some(coordinate) = frontier.pop() {
+                for neighbour in self.surrounding_walkable_coordinates(&coordinate) {
+                    if visited.insert(neighbour) {
+                        coordinates.push(neighbour);
+                        frontier.push(neighbour);
+                    }
+                }
+            }
+
+            regions.push(Region::from(coordinates));
+        }
+
+        regions.sort_by_key(|region| std::cmp::Reverse(region.size()));
+        regions
+    }
+
+    pub fn carve(&mut self, coordinate: &Coordinate) {
+        let cell = match self.cells.get_mut(coordinate) {
+            Some(it) if !it.is_walkable() && !self.blocked.contains(coordinate) => it,
+            _ => return,
+        };
+
+        let layer = if cell
+            .layers()
+            .contains(&LayerType::Structure(StructureType::Wall))
+        {
+            LayerType::Structure(StructureType::Door)
+        } else if cell.floor_type() == Some(FloorType::Indoor) {
+            LayerType::Floor(FloorType::Indoor)
+        } else {
+            LayerType::Floor(FloorType::Outdoor)
+        };
+
+        cell.clear_contents();
+        cell.add_layer(layer);
+
+        for room in self.rooms.iter_mut() {
+            room.replace_cell_contents(coordinate.x(), coordinate.y(), layer);
+        }
+    }
+
+    // Nothing can be dug up from under a wall, boulder or anything else still
+    // standing in the way, it has to be destroyed first.
+    pub fn dig(&mut self, coordinate: &Coordinate) -> Option<GridEvent> {
+        let cell = self.cells.get_mut(coordinate)?;
+        if cell
+            .layers()
+            .iter()
+            .any(|layer| matches!(layer, LayerType::Structure(_)) && !layer.is_walkable())
+        {
+            return None;
+        }
+
+        let layer_type = cell.reveal_buried_layer()?;
+        self.sync_rooms(coordinate);
+
+        Some(GridEvent::LayerRevealed {
+            coordinate: *coordinate,
+            layer_type,
+        })
+    }
+
+    // Anything standing on the cell comes down with the structure, so a torch
+    // doesn't outlive the wall it hangs on.
+    pub fn destroy_structure(&mut self, coordinate: &Coordinate) -> Option<GridEvent> {
+        let cell = self.cells.get_mut(coordinate)?;
+        let layer_type = cell
+            .layers()
+            .iter()
+            .rev()
+            .find(|layer| {
+                matches!(layer, LayerType::Structure(structure_type)
+                    if !matches!(structure_type, StructureType::Rubble | StructureType::Torch))
+            })
+            .copied()?;
+
+        let structures: Vec<LayerType> = cell
+            .layers()
+            .iter()
+            .filter(|layer| matches!(layer, LayerType::Structure(_)))
+            .copied()
+            .collect();
+        for structure in structures.iter() {
+            cell.remove_layer(structure);
+        }
+        if cell.floor_type().is_none() {
+            cell.add_layer(LayerType::Floor(FloorType::Outdoor));
+        }
+        cell.add_layer(LayerType::Structure(StructureType::Rubble));
+        self.sync_rooms(coordinate);
+
+        Some(GridEvent::StructureDestroyed {
+            coordinate: *coordinate,
+            layer_type,
+        })
+    }
+
+    // Locked and secret doors need more than a push, so only plain doors
+    // toggle.
+    pub fn toggle_door(&mut self, coordinate: &Coordinate) -> Option<GridEvent> {
+        let cell = self.cells.get_mut(coordinate)?;
+        let closed = LayerType::Structure(StructureType::Door);
+        let open = LayerType::Structure(StructureType::OpenDoor);
+
+        let opened = if cell.layers().contains(&closed) {
+            cell.replace_layer(&closed, open);
+            true
+        } else if cell.layers().contains(&open) {
+            cell.replace_layer(&open, closed);
+            false
+        } else {
+            return None;
+        };
+        self.sync_rooms(coordinate);
+
+        Some(GridEvent::DoorToggled {
+            coordinate: *coordinate,
+            open: opened,
+        })
+    }
+
+    fn sync_rooms(&mut self, coordinate: &Coordinate) {
+        if let Some(cell) = self.cells.get(coordinate) {
+            for room in self.rooms.iter_mut() {
+                room.replace_cell(cell);
+            }
+        }
+    }
+
+    pub fn diff(&self, other: &Grid) -> GridDiff {
+        GridDiff::between(self, other)
+    }
+
+    pub fn apply_diff(&mut self, diff: &GridDiff) {
+        if let Some(size) = diff.size {
+            self.size = size;
+        }
+
+        for change in diff.cells.iter() {
+            match &change.layers {
+                Some(layers) => self.add_cell(Cell::from((change.coordinate, layers.clone()))),
+                None => {
+                    self.cells.remove(&change.coordinate);
+                }
+            }
+        }
+
+        for change in diff.rooms.iter() {
+            match self.rooms.get_mut(change.index) {
+                Some(room) => *room = change.room.clone(),
+                None => self.rooms.push(change.room.clone()),
+            }
+        }
+        if let Some(room_count) = diff.room_count {
+            self.rooms.truncate(room_count);
+        }
+
+        self.blocked.extend(diff.blocked.iter().copied());
+        for coordinate in diff.unblocked.iter() {
+            self.blocked.remove(coordinate);
+        }
+    }
+
+    pub fn fill_with_rock(&mut self, coordinate: &Coordinate) {
+        if self.blocked.contains(coordinate) {
+            return;
+        }
+
+        if let Some(cell) = self.cells.get_mut(coordinate) {
+            cell.clear_contents();
+            cell.add_layer(LayerType::Structure(StructureType::Boulder));
+        }
+    }
+
     pub fn add_room(&mut self, room: Room) {
         for cell in room.cells().iter() {
             if let Some(grid_cell) = self.cells.get_mut(cell.coordinate()) {
@@ -76,14 +313,42 @@ impl Grid {
                 }
             }
         }
+
+        self.rooms.push(room);
     }
 
     pub fn fill_empty_cells(&mut self) {
-        for cell in self.cells.values_mut().filter(|c| c.is_empty()) {
+        for cell in self
+            .cells
+            .values_mut()
+            .filter(|c| c.is_empty() && !self.blocked.contains(c.coordinate()))
+        {
             cell.set_to_floor();
         }
     }
 
+    pub fn block(&mut self, coordinate: &Coordinate) {
+        self.blocked.insert(*coordinate);
+    }
+
+    pub fn unblock(&mut self, coordinate: &Coordinate) {
+        self.blocked.remove(coordinate);
+    }
+
+    pub fn is_blocked(&self, coordinate: &Coordinate) -> bool {
+        self.blocked.contains(coordinate)
+    }
+
+    pub fn blocked_coordinates(&self) -> &HashSet<Coordinate> {
+        &self.blocked
+    }
+
+    pub fn replace_cell(&mut self, cell: Cell) {
+        if self.cells.contains_key(cell.coordinate()) {
+            self.add_cell(cell);
+        }
+    }
+
     pub fn is_cell_empty(&self, coordinate: &Coordinate) -> bool {
         let cell = self.cells.get(coordinate);
         match cell {
@@ -93,19 +358,26 @@ impl Grid {
     }
 
     pub fn random_coordinate(&mut self) -> Option<Coordinate> {
-        let mut coordinates: Vec<Coordinate> = self.cells.keys().copied().collect();
+        self.random_unblocked_coordinate()
+    }
 
+    pub fn unblocked_coordinates(&self) -> Vec<Coordinate> {
+        let mut coordinates: Vec<Coordinate> = self
+            .cells
+            .keys()
+            .filter(|coordinate| !self.blocked.contains(coordinate))
+            .copied()
+            .collect();
         coordinates.sort_by_key(|coordinate| (coordinate.x(), coordinate.y()));
-        let index: usize = self.rng.gen_range(0..coordinates.len());
-        coordinates.get(index).copied()
+        coordinates
     }
 
-    pub fn random_spawnable_coordinate(&mut self) -> Option<Coordinate> {
+    pub fn spawnable_coordinates(&self) -> Vec<Coordinate> {
         let mut spawnable_cells: Vec<Coordinate> = self
             .cells
             .iter()
             .filter_map(|(coordinate, cell)| {
-                if cell.is_spawnable() {
+                if cell.is_spawnable() && !self.blocked.contains(coordinate) {
                     Some(*coordinate)
                 } else {
                     None
@@ -113,6 +385,15 @@ impl Grid {
             })
             .collect();
         spawnable_cells.sort_by_key(|coordinate| (coordinate.x(), coordinate.y()));
+        spawnable_cells
+    }
+
+    pub fn random_spawnable_coordinate(&mut self) -> Option<Coordinate> {
+        let spawnable_cells = self.spawnable_coordinates();
+        if spawnable_cells.is_empty() {
+            return None;
+        }
+
         let index: usize = self.rng.gen_range(0..spawnable_cells.len());
         spawnable_cells.get(index).copied()
     }
@@ -129,6 +410,17 @@ impl Grid {
         }
     }
 
+    pub fn replace_layer(
+        &mut self,
+        coordinate: &Coordinate,
+        existing: &LayerType,
+        replacement: LayerType,
+    ) {
+        if let Some(cell) = self.cells.get_mut(coordinate) {
+            cell.replace_layer(existing, replacement)
+        }
+    }
+
     pub fn bury_layer(&mut self, coordinate: &Coordinate, layer: LayerType) {
         if let Some(cell) = self.cells.get_mut(coordinate) {
             cell.bury_layer(&layer)
@@ -136,12 +428,11 @@ impl Grid {
     }
 
     pub fn random_unblocked_coordinate(&mut self) -> Option<Coordinate> {
-        let mut coordinates: Vec<Coordinate> = self
-            .cells
-            .iter_mut()
-            .map(|(coordinate, _)| *coordinate)
-            .collect();
-        coordinates.sort_by_key(|coordinate| (coordinate.x(), coordinate.y()));
+        let coordinates = self.unblocked_coordinates();
+        if coordinates.is_empty() {
+            return None;
+        }
+
         let index: usize = self.rng.gen_range(0..coordinates.len());
         coordinates.get(index).copied()
     }
@@ -177,6 +468,8 @@ impl Grid {
         let mut grid = Self {
             size,
             cells: HashMap::default(),
+            rooms: Vec::new(),
+            blocked: HashSet::new(),
             rng,
         };
 
@@ -189,3 +482,167 @@ impl Grid {
         grid
     }
 }
+
+// The RNG is generation state rather than part of the map, and cells are
+// written in coordinate order so the same map always serializes the same way.
+impl Serialize for Grid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut cells: Vec<&Cell> = self.cells.values().collect();
+        cells.sort_by_key(|cell| *cell.coordinate());
+        let mut blocked: Vec<&Coordinate> = self.blocked.iter().collect();
+        blocked.sort();
+
+        let mut state = serializer.serialize_struct("Grid", 4)?;
+        state.serialize_field("size", &self.size)?;
+        state.serialize_field("cells", &cells)?;
+        state.serialize_field("rooms", &self.rooms)?;
+        state.serialize_field("blocked", &blocked)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::builder,
+        map::{
+            cells::Coordinate,
+            layers::{FloorType, ItemRarity, LayerType, StructureType},
+            GridEvent,
+        },
+        test_support::open_grid,
+    };
+
+    #[test]
+    fn digging_reveals_buried_layers() {
+        let mut grid = open_grid(3);
+        let coordinate = Coordinate::from(1);
+        grid.bury_layer(&coordinate, LayerType::Item(ItemRarity::Exotic));
+
+        assert_eq!(
+            grid.dig(&coordinate),
+            Some(GridEvent::LayerRevealed {
+                coordinate,
+                layer_type: LayerType::Item(ItemRarity::Exotic),
+            })
+        );
+        assert_eq!(
+            grid.cell(&coordinate).unwrap().visible_layer(),
+            LayerType::Item(ItemRarity::Exotic)
+        );
+        assert_eq!(grid.dig(&coordinate), None);
+        assert_eq!(grid.dig(&Coordinate::from(10)), None);
+    }
+
+    #[test]
+    fn walls_have_to_come_down_before_digging() {
+        let mut grid = open_grid(3);
+        let coordinate = Coordinate::from(1);
+        grid.bury_layer(&coordinate, LayerType::Key);
+        grid.add_layer(&coordinate, LayerType::Structure(StructureType::Wall));
+        let layers = grid.cell(&coordinate).unwrap().layers().clone();
+
+        assert_eq!(grid.dig(&coordinate), None);
+        assert_eq!(grid.cell(&coordinate).unwrap().layers(), &layers);
+
+        grid.destroy_structure(&coordinate);
+        assert_eq!(
+            grid.dig(&coordinate),
+            Some(GridEvent::LayerRevealed {
+                coordinate,
+                layer_type: LayerType::Key,
+            })
+        );
+        let cell = grid.cell(&coordinate).unwrap();
+        assert!(cell.is_walkable());
+        assert!(!cell.is_opaque());
+    }
+
+    #[test]
+    fn destroyed_walls_become_walkable_rubble() {
+        let mut grid = open_grid(3);
+        let coordinate = Coordinate::from(1);
+        grid.add_layer(&coordinate, LayerType::Structure(StructureType::Wall));
+        grid.add_layer(&coordinate, LayerType::Structure(StructureType::Torch));
+        assert!(!grid.cell(&coordinate).unwrap().is_walkable());
+
+        assert_eq!(
+            grid.destroy_structure(&coordinate),
+            Some(GridEvent::StructureDestroyed {
+                coordinate,
+                layer_type: LayerType::Structure(StructureType::Wall),
+            })
+        );
+
+        let cell = grid.cell(&coordinate).unwrap();
+        assert_eq!(
+            cell.layers(),
+            &vec![
+                LayerType::Floor(FloorType::Outdoor),
+                LayerType::Floor(FloorType::Indoor),
+                LayerType::Structure(StructureType::Rubble),
+            ]
+        );
+        assert!(cell.is_walkable());
+        assert_eq!(grid.destroy_structure(&coordinate), None);
+    }
+
+    #[test]
+    fn operations_keep_rooms_in_step() {
+        let mut map = builder()
+            .seed("operations")
+            .grid_size(NonZeroU16::new(30).unwrap())
+            .build()
+            .generate_top_down_map()
+            .unwrap();
+        let room = map.grid().rooms()[0].clone();
+        let door = *room
+            .cells()
+            .iter()
+            .find(|cell| cell.visible_layer() == LayerType::Structure(StructureType::Door))
+            .unwrap()
+            .coordinate();
+        let wall = *room
+            .cells()
+            .iter()
+            .find(|cell| cell.visible_layer() == LayerType::Structure(StructureType::Wall))
+            .unwrap()
+            .coordinate();
+        let grid = map.grid_mut();
+
+        assert_eq!(
+            grid.toggle_door(&door),
+            Some(GridEvent::DoorToggled {
+                coordinate: door,
+                open: true,
+            })
+        );
+        grid.destroy_structure(&wall);
+
+        let room = &grid.rooms()[0];
+        for coordinate in [door, wall] {
+            assert_eq!(
+                room.cell_at(coordinate.x(), coordinate.y()),
+                grid.cell(&coordinate)
+            );
+        }
+        assert_eq!(
+            grid.cell(&door).unwrap().visible_layer(),
+            LayerType::Structure(StructureType::OpenDoor)
+        );
+        assert_eq!(
+            grid.cell(&wall).unwrap().visible_layer(),
+            LayerType::Structure(StructureType::Rubble)
+        );
+        assert_eq!(
+            grid.toggle_door(&door),
+            Some(GridEvent::DoorToggled {
+                coordinate: door,
+                open: false,
+            })
+        );
+        assert_eq!(grid.toggle_door(&wall), None);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{cells::Coordinate, layers::LayerType, Grid, Room};
+
+// The layers a cell ends up with, or None when the cell was removed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CellChange {
+    pub coordinate: Coordinate,
+    pub layers: Option<Vec<LayerType>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RoomChange {
+    pub index: usize,
+    pub room: Room,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GridDiff {
+    pub size: Option<u16>,
+    pub cells: Vec<CellChange>,
+    pub rooms: Vec<RoomChange>,
+    pub room_count: Option<usize>,
+    pub blocked: Vec<Coordinate>,
+    pub unblocked: Vec<Coordinate>,
+}
+
+impl GridDiff {
+    pub fn between(before: &Grid, after: &Grid) -> Self {
+        let before_cells: HashMap<&Coordinate, &Vec<LayerType>> = before
+            .cells()
+            .into_iter()
+            .map(|cell| (cell.coordinate(), cell.layers()))
+            .collect();
+        let after_cells: HashMap<&Coordinate, &Vec<LayerType>> = after
+            .cells()
+            .into_iter()
+            .map(|cell| (cell.coordinate(), cell.layers()))
+            .collect();
+
+        let mut cells: Vec<CellChange> = after_cells
+            .iter()
+            .filter(|(coordinate, layers)| before_cells.get(*coordinate) != Some(*layers))
+            .map(|(coordinate, layers)| CellChange {
+                coordinate: **coordinate,
+                layers: Some((*layers).clone()),
+            })
+            .chain(
+                before_cells
+                    .keys()
+                    .filter(|coordinate| !after_cells.contains_key(*coordinate))
+                    .map(|coordinate| CellChange {
+                        coordinate: **coordinate,
+                        layers: None,
+                    }),
+            )
+            .collect();
+        cells.sort_by_key(|change| change.coordinate);
+
+        let rooms: Vec<RoomChange> = after
+            .rooms()
+            .iter()
+            .enumerate()
+            .filter(|(index, room)| before.rooms().get(*index) != Some(*room))
+            .map(|(index, room)| RoomChange {
+                index,
+                room: room.clone(),
+            })
+            .collect();
+
+        let mut blocked: Vec<Coordinate> = after
+            .blocked_coordinates()
+            .difference(before.blocked_coordinates())
+            .copied()
+            .collect();
+        blocked.sort();
+        let mut unblocked: Vec<Coordinate> = before
+            .blocked_coordinates()
+            .difference(after.blocked_coordinates())
+            .copied()
+            .collect();
+        unblocked.sort();
+
+        Self {
+            size: (before.size() != after.size()).then_some(after.size()),
+            cells,
+            rooms,
+            room_count: (before.rooms().len() != after.rooms().len())
+                .then_some(after.rooms().len()),
+            blocked,
+            unblocked,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn changed_coordinates(&self) -> HashSet<Coordinate> {
+        self.cells.iter().map(|change| change.coordinate).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{
+        map::{
+            cells::Coordinate,
+            layers::{ItemRarity, LayerType, StructureType},
+            Grid,
+        },
+        test_support::map,
+    };
+
+    use super::GridDiff;
+
+    fn snapshot(grid: &Grid) -> Value {
+        serde_json::to_value(grid).unwrap()
+    }
+
+    #[test]
+    fn applying_a_diff_rebuilds_the_target() {
+        let mut before = map("diff", 30);
+        let mut after = map("diff", 30);
+        let grid = after.grid_mut();
+        let (door, wall) = {
+            let room = &grid.rooms()[0];
+            let find = |layer_type| {
+                *room
+                    .cells()
+                    .iter()
+                    .find(|cell| cell.visible_layer() == layer_type)
+                    .unwrap()
+                    .coordinate()
+            };
+            (
+                find(LayerType::Structure(StructureType::Door)),
+                find(LayerType::Structure(StructureType::Wall)),
+            )
+        };
+        grid.toggle_door(&door);
+        grid.destroy_structure(&wall);
+        grid.add_layer(&Coordinate::from(3), LayerType::Item(ItemRarity::Rare));
+        grid.block(&Coordinate::from(4));
+
+        let diff = before.grid().diff(after.grid());
+        assert_eq!(diff.changed_coordinates().len(), 3);
+        assert!(!diff.rooms.is_empty());
+
+        let serialized = serde_json::to_string(&diff).unwrap();
+        let diff: GridDiff = serde_json::from_str(&serialized).unwrap();
+        before.grid_mut().apply_diff(&diff);
+
+        assert_eq!(snapshot(before.grid()), snapshot(after.grid()));
+        assert!(before.grid().diff(after.grid()).is_empty());
+    }
+
+    #[test]
+    fn diffs_between_unrelated_grids_apply_cleanly() {
+        for (from, to) in [(("one", 30), ("two", 40)), (("three", 40), ("four", 25))] {
+            let mut before = map(from.0, from.1);
+            let after = map(to.0, to.1);
+
+            let diff = before.grid().diff(after.grid());
+            assert!(diff.size.is_some());
+            before.grid_mut().apply_diff(&diff);
+
+            assert_eq!(snapshot(before.grid()), snapshot(after.grid()));
+        }
+    }
+}
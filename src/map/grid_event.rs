@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use super::{cells::Coordinate, layers::LayerType};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridEvent {
+    LayerRevealed {
+        coordinate: Coordinate,
+        layer_type: LayerType,
+    },
+    StructureDestroyed {
+        coordinate: Coordinate,
+        layer_type: LayerType,
+    },
+    DoorToggled {
+        coordinate: Coordinate,
+        open: bool,
+    },
+}
+
+impl GridEvent {
+    pub fn coordinate(&self) -> &Coordinate {
+        match self {
+            GridEvent::LayerRevealed { coordinate, .. } => coordinate,
+            GridEvent::StructureDestroyed { coordinate, .. } => coordinate,
+            GridEvent::DoorToggled { coordinate, .. } => coordinate,
+        }
+    }
+}
@@ -2,35 +2,35 @@ use serde::{Deserialize, Serialize};
 
 use super::{item_rarity::ItemRarity, FloorType, StructureType};
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LayerType {
+    #[default]
     Empty,
     Entrance,
     Exit,
     Item(ItemRarity),
     Floor(FloorType),
+    Key,
     Note,
     Path,
     Structure(StructureType),
 }
 
-impl Default for LayerType {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 impl From<LayerType> for char {
     fn from(val: LayerType) -> Self {
         match val {
             LayerType::Item(ItemRarity::Common) => 'c',
             LayerType::Structure(StructureType::Door) => 'D',
+            LayerType::Structure(StructureType::LockedDoor) => 'L',
+            LayerType::Structure(StructureType::OpenDoor) => '/',
+            LayerType::Structure(StructureType::SecretDoor) => '#',
             LayerType::Empty => '_',
             LayerType::Entrance => 'o',
             LayerType::Exit => 'x',
             LayerType::Item(ItemRarity::Exotic) => 'i',
             LayerType::Floor(FloorType::Outdoor) => '·',
+            LayerType::Key => 'k',
             LayerType::Note => 'n',
             LayerType::Structure(StructureType::Boulder) => '*',
             LayerType::Path => '♦',
@@ -40,6 +40,7 @@ impl From<LayerType> for char {
             LayerType::Structure(StructureType::Rubble) => '&',
             LayerType::Structure(StructureType::Other) => 's',
             LayerType::Structure(StructureType::Table) => '¬',
+            LayerType::Structure(StructureType::Torch) => 't',
             LayerType::Structure(StructureType::Rocks) => '.',
             LayerType::Item(ItemRarity::Uncommon) => 'u',
         }
@@ -51,11 +52,15 @@ impl From<char> for LayerType {
         match c {
             'c' => LayerType::Item(ItemRarity::Common),
             'D' => LayerType::Structure(StructureType::Door),
+            'L' => LayerType::Structure(StructureType::LockedDoor),
+            '/' => LayerType::Structure(StructureType::OpenDoor),
+            '#' => LayerType::Structure(StructureType::SecretDoor),
             '_' => LayerType::Empty,
             'o' => LayerType::Entrance,
             'x' => LayerType::Exit,
             'i' => LayerType::Item(ItemRarity::Exotic),
             '·' => LayerType::Floor(FloorType::Outdoor),
+            'k' => LayerType::Key,
             'n' => LayerType::Note,
             '*' => LayerType::Structure(StructureType::Boulder),
             '♦' => LayerType::Path,
@@ -65,6 +70,7 @@ impl From<char> for LayerType {
             '&' => LayerType::Structure(StructureType::Rubble),
             's' => LayerType::Structure(StructureType::Other),
             '¬' => LayerType::Structure(StructureType::Table),
+            't' => LayerType::Structure(StructureType::Torch),
             'u' => LayerType::Item(ItemRarity::Uncommon),
             '.' => LayerType::Structure(StructureType::Rocks),
             _ => LayerType::Empty,
@@ -73,6 +79,32 @@ impl From<char> for LayerType {
 }
 
 impl LayerType {
+    pub const ALL: [LayerType; 23] = [
+        LayerType::Empty,
+        LayerType::Entrance,
+        LayerType::Exit,
+        LayerType::Item(ItemRarity::Common),
+        LayerType::Item(ItemRarity::Uncommon),
+        LayerType::Item(ItemRarity::Rare),
+        LayerType::Item(ItemRarity::Exotic),
+        LayerType::Floor(FloorType::Indoor),
+        LayerType::Floor(FloorType::Outdoor),
+        LayerType::Key,
+        LayerType::Note,
+        LayerType::Path,
+        LayerType::Structure(StructureType::Boulder),
+        LayerType::Structure(StructureType::Door),
+        LayerType::Structure(StructureType::LockedDoor),
+        LayerType::Structure(StructureType::OpenDoor),
+        LayerType::Structure(StructureType::Other),
+        LayerType::Structure(StructureType::Rocks),
+        LayerType::Structure(StructureType::Rubble),
+        LayerType::Structure(StructureType::SecretDoor),
+        LayerType::Structure(StructureType::Table),
+        LayerType::Structure(StructureType::Wall),
+        LayerType::Structure(StructureType::Torch),
+    ];
+
     pub fn can_bury_other_layers() -> Vec<LayerType> {
         vec![
             LayerType::Floor(FloorType::Outdoor),
@@ -84,6 +116,7 @@ impl LayerType {
     pub fn is_walkable(&self) -> bool {
         let walkable_types = vec![
             LayerType::Structure(StructureType::Door),
+            LayerType::Structure(StructureType::OpenDoor),
             LayerType::Floor(FloorType::Indoor),
             LayerType::Floor(FloorType::Outdoor),
             LayerType::Structure(StructureType::Rubble),
@@ -91,6 +124,7 @@ impl LayerType {
             LayerType::Item(ItemRarity::Uncommon),
             LayerType::Item(ItemRarity::Rare),
             LayerType::Item(ItemRarity::Exotic),
+            LayerType::Key,
             LayerType::Note,
             LayerType::Path,
             LayerType::Entrance,
@@ -100,16 +134,40 @@ impl LayerType {
     }
 
     pub fn is_spawnable(&self) -> bool {
-        let spawnable_types = vec![
+        let spawnable_types = [
             LayerType::Floor(FloorType::Indoor),
             LayerType::Floor(FloorType::Outdoor),
         ];
         spawnable_types.contains(self)
     }
 
+    pub fn is_door(&self) -> bool {
+        matches!(self, LayerType::Structure(structure_type) if structure_type.is_door())
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        matches!(
+            self,
+            LayerType::Structure(
+                StructureType::Wall
+                    | StructureType::Boulder
+                    | StructureType::Door
+                    | StructureType::LockedDoor
+                    | StructureType::SecretDoor
+            )
+        )
+    }
+
     pub fn is_completely_obstructed(&self) -> bool {
         self == &LayerType::Structure(StructureType::Boulder) || self == &LayerType::Empty
     }
+
+    pub fn movement_cost(&self) -> u32 {
+        match self {
+            LayerType::Structure(StructureType::Rubble) => 3,
+            _ => 1,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,13 +1,29 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum StructureType {
     Boulder,
     Door,
+    LockedDoor,
+    OpenDoor,
     Other,
     Rocks,
     Rubble,
+    SecretDoor,
     Table,
+    Torch,
     Wall,
 }
+
+impl StructureType {
+    pub fn is_door(&self) -> bool {
+        matches!(
+            self,
+            StructureType::Door
+                | StructureType::LockedDoor
+                | StructureType::OpenDoor
+                | StructureType::SecretDoor
+        )
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cells::Coordinate,
+    layers::{FloorType, LayerType, StructureType},
+    FieldOfView, Grid,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct LightSource {
+    pub coordinate: Coordinate,
+    pub radius: u32,
+    pub intensity: f32,
+}
+
+impl LightSource {
+    pub fn new(coordinate: Coordinate, radius: u32, intensity: f32) -> Self {
+        Self {
+            coordinate,
+            radius,
+            intensity,
+        }
+    }
+
+    // Torches hang on walls, and a wall doesn't stop the view from its own
+    // cell, so the light is cast from the indoor floor the torch faces.
+    // Torches with no such floor next to them light from where they are.
+    pub fn torches(grid: &Grid, radius: u32, intensity: f32) -> Vec<Self> {
+        let mut coordinates: Vec<Coordinate> = grid
+            .cells()
+            .into_iter()
+            .filter(|cell| {
+                cell.layers()
+                    .contains(&LayerType::Structure(StructureType::Torch))
+            })
+            .map(|cell| {
+                cell.coordinate()
+                    .neighbours()
+                    .into_iter()
+                    .find(|neighbour| {
+                        grid.cell(neighbour).is_some_and(|cell| {
+                            cell.is_walkable() && cell.floor_type() == Some(FloorType::Indoor)
+                        })
+                    })
+                    .unwrap_or(*cell.coordinate())
+            })
+            .collect();
+        coordinates.sort();
+
+        coordinates
+            .into_iter()
+            .map(|coordinate| Self::new(coordinate, radius, intensity))
+            .collect()
+    }
+
+    // Full intensity on the source, fading linearly to nothing just past the
+    // radius.
+    pub fn brightness_at(&self, coordinate: &Coordinate) -> f32 {
+        let dx = (coordinate.x() - self.coordinate.x()) as f32;
+        let dy = (coordinate.y() - self.coordinate.y()) as f32;
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        (self.intensity * (1.0 - distance / (self.radius as f32 + 1.0))).max(0.0)
+    }
+}
+
+// Levels are stored row by row from the grid's bounds and capped at 1.0 where
+// lights overlap.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct LightMap {
+    min: Coordinate,
+    width: usize,
+    height: usize,
+    levels: Vec<f32>,
+}
+
+impl LightMap {
+    pub fn build(grid: &Grid, sources: &[LightSource]) -> Self {
+        let (min, max) = grid.bounds();
+        let width = (max.x() - min.x() + 1) as usize;
+        let height = (max.y() - min.y() + 1) as usize;
+        let mut light_map = Self {
+            min,
+            width,
+            height,
+            levels: vec![0.0; width * height],
+        };
+
+        for source in sources.iter() {
+            for coordinate in FieldOfView::build(grid, &source.coordinate, source.radius).visible()
+            {
+                if let Some(index) = light_map.index(coordinate) {
+                    let level = &mut light_map.levels[index];
+                    *level = (*level + source.brightness_at(coordinate)).min(1.0);
+                }
+            }
+        }
+
+        light_map
+    }
+
+    pub fn from_torches(grid: &Grid, radius: u32, intensity: f32) -> Self {
+        Self::build(grid, &LightSource::torches(grid, radius, intensity))
+    }
+
+    pub fn level(&self, coordinate: &Coordinate) -> f32 {
+        self.index(coordinate)
+            .map(|index| self.levels[index])
+            .unwrap_or_default()
+    }
+
+    pub fn is_lit(&self, coordinate: &Coordinate) -> bool {
+        self.level(coordinate) > 0.0
+    }
+
+    pub fn min(&self) -> &Coordinate {
+        &self.min
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn levels(&self) -> &Vec<f32> {
+        &self.levels
+    }
+
+    pub fn row(&self, y: i32) -> Option<&[f32]> {
+        let row = usize::try_from(y - self.min.y()).ok()?;
+        (row < self.height).then(|| &self.levels[row * self.width..(row + 1) * self.width])
+    }
+
+    fn index(&self, coordinate: &Coordinate) -> Option<usize> {
+        let x = usize::try_from(coordinate.x() - self.min.x()).ok()?;
+        let y = usize::try_from(coordinate.y() - self.min.y()).ok()?;
+
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::builder,
+        map::{
+            cells::Coordinate,
+            layers::{FloorType, LayerType, StructureType},
+        },
+        test_support::open_grid,
+    };
+
+    use super::{LightMap, LightSource};
+
+    #[test]
+    fn light_falls_off_and_stops_at_walls() {
+        let mut grid = open_grid(12);
+        for y in 0..12 {
+            grid.add_layer(
+                &Coordinate::from((6, y)),
+                LayerType::Structure(StructureType::Wall),
+            );
+        }
+
+        let source = LightSource::new(Coordinate::from((3, 5)), 5, 1.0);
+        let light_map = LightMap::build(&grid, &[source]);
+
+        assert_eq!(light_map.level(&Coordinate::from((3, 5))), 1.0);
+        assert!(
+            light_map.level(&Coordinate::from((4, 5))) > light_map.level(&Coordinate::from((5, 5)))
+        );
+        assert!(light_map.is_lit(&Coordinate::from((6, 5))));
+        assert!(!light_map.is_lit(&Coordinate::from((7, 5))));
+        assert!(!light_map.is_lit(&Coordinate::from((3, 11))));
+        assert_eq!(light_map.row(5).unwrap().len(), 12);
+        assert_eq!(
+            light_map.row(5).unwrap()[4],
+            light_map.level(&Coordinate::from((4, 5)))
+        );
+        assert!(light_map.row(12).is_none());
+    }
+
+    #[test]
+    fn torches_only_light_the_side_they_face() {
+        let mut grid = open_grid(12);
+        for y in 0..12 {
+            grid.add_layer(
+                &Coordinate::from((6, y)),
+                LayerType::Structure(StructureType::Wall),
+            );
+        }
+        grid.replace_layer(
+            &Coordinate::from((5, 5)),
+            &LayerType::Floor(FloorType::Outdoor),
+            LayerType::Floor(FloorType::Indoor),
+        );
+        grid.add_layer(
+            &Coordinate::from((6, 5)),
+            LayerType::Structure(StructureType::Torch),
+        );
+
+        let light_map = LightMap::from_torches(&grid, 4, 1.0);
+
+        assert_eq!(light_map.level(&Coordinate::from((5, 5))), 1.0);
+        assert!(light_map.is_lit(&Coordinate::from((6, 5))));
+        assert!(light_map.is_lit(&Coordinate::from((3, 5))));
+        for y in 0..12 {
+            for x in 7..12 {
+                assert!(!light_map.is_lit(&Coordinate::from((x, y))));
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_lights_add_up_to_full_brightness() {
+        let grid = open_grid(9);
+        let dim = |x| LightSource::new(Coordinate::from((x, 4)), 4, 0.4);
+
+        let one = LightMap::build(&grid, &[dim(2)]);
+        let two = LightMap::build(&grid, &[dim(2), dim(6)]);
+        let many = LightMap::build(&grid, &[dim(4), dim(4), dim(4)]);
+
+        let between = Coordinate::from(4);
+        assert!(two.level(&between) > one.level(&between));
+        assert_eq!(many.level(&between), 1.0);
+    }
+
+    #[test]
+    fn torches_light_the_rooms_they_hang_in() {
+        let map = builder()
+            .seed("torches")
+            .grid_size(NonZeroU16::new(40).unwrap())
+            .torch_spacing(NonZeroU16::new(5).unwrap())
+            .build()
+            .generate_top_down_map()
+            .unwrap();
+
+        let light_map = LightMap::from_torches(map.grid(), 4, 1.0);
+        let lit_floor = map
+            .grid()
+            .cells()
+            .into_iter()
+            .filter(|cell| cell.is_walkable() && light_map.is_lit(cell.coordinate()))
+            .count();
+
+        assert!(lit_floor > 0);
+        assert!(light_map
+            .levels()
+            .iter()
+            .all(|level| (0.0..=1.0).contains(level)));
+    }
+}
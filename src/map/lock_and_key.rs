@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+use super::cells::Coordinate;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockAndKey {
+    pub lock: Coordinate,
+    pub key: Coordinate,
+}
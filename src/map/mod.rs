@@ -1,10 +1,29 @@
+pub mod autotile;
 mod biome;
 pub mod cells;
+mod distance_map;
+mod door_policy;
+mod exploration;
+pub mod field_of_view;
 mod grid;
+mod grid_diff;
+mod grid_event;
 pub mod layers;
+mod light_map;
+mod lock_and_key;
+mod region;
 mod room;
 mod top_down_map;
 
+pub use distance_map::DistanceMap;
+pub use door_policy::DoorPolicy;
+pub use exploration::{Exploration, Visibility};
+pub use field_of_view::FieldOfView;
 pub use grid::Grid;
+pub use grid_diff::{CellChange, GridDiff, RoomChange};
+pub use grid_event::GridEvent;
+pub use light_map::{LightMap, LightSource};
+pub use lock_and_key::LockAndKey;
+pub use region::Region;
 pub use room::Room;
 pub use top_down_map::TopDownMap;
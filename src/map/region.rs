@@ -0,0 +1,27 @@
+use super::cells::Coordinate;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    coordinates: Vec<Coordinate>,
+}
+
+impl From<Vec<Coordinate>> for Region {
+    fn from(mut coordinates: Vec<Coordinate>) -> Self {
+        coordinates.sort();
+        Self { coordinates }
+    }
+}
+
+impl Region {
+    pub fn coordinates(&self) -> &Vec<Coordinate> {
+        &self.coordinates
+    }
+
+    pub fn size(&self) -> usize {
+        self.coordinates.len()
+    }
+
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        self.coordinates.binary_search(coordinate).is_ok()
+    }
+}
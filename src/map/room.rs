@@ -1,21 +1,29 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    cells::Cell,
+    cells::{Cell, Coordinate},
     layers::{FloorType, LayerType, StructureType},
+    DoorPolicy,
 };
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Room {
     cells: Vec<Cell>,
     max_side_length: u16,
+    #[serde(default)]
+    door_policy: Option<DoorPolicy>,
 }
 
 impl From<&String> for Room {
     fn from(value: &String) -> Self {
         let mut max_side_length: u16 = 0;
         let mut cells: Vec<Cell> = Vec::new();
-        let mut lines: Vec<&str> = value.split('\n').collect();
+        // Only the leading `@` lines are a header, the same ones
+        // `DoorPolicy::from_template_header` reads.
+        let mut lines: Vec<&str> = value
+            .split('\n')
+            .skip_while(|line| line.trim().is_empty() || line.trim().starts_with('@'))
+            .collect();
         // Need to reverse so that we get the bottom ones as the first
         // cells that we create.
         lines.reverse();
@@ -50,9 +58,12 @@ impl From<&String> for Room {
             max_side_length = y_side_length;
         }
 
+        // A header that doesn't parse is left out here, `Room::from_template`
+        // reports it.
         Room {
             cells,
             max_side_length,
+            door_policy: DoorPolicy::from_template_header(value).ok().flatten(),
         }
     }
 }
@@ -70,6 +81,13 @@ impl From<String> for Room {
 }
 
 impl Room {
+    pub fn from_template(template: &str) -> Result<Self, String> {
+        let door_policy = DoorPolicy::from_template_header(template)?;
+        let mut room = Self::from(template);
+        room.door_policy = door_policy;
+        Ok(room)
+    }
+
     pub fn cells(&self) -> &Vec<Cell> {
         &self.cells
     }
@@ -93,9 +111,40 @@ impl Room {
         Self {
             cells,
             max_side_length: self.max_side_length,
+            door_policy: self.door_policy.clone(),
         }
     }
 
+    pub fn door_policy(&self) -> Option<&DoorPolicy> {
+        self.door_policy.as_ref()
+    }
+
+    pub fn with_door_policy(mut self, door_policy: DoorPolicy) -> Self {
+        self.door_policy = Some(door_policy);
+        self
+    }
+
+    pub fn has_doors(&self) -> bool {
+        self.cells.iter().any(|cell| cell.contains_door())
+    }
+
+    pub fn bounds(&self) -> (Coordinate, Coordinate) {
+        let min_x = self.cells.iter().map(|cell| cell.coordinate().x()).min();
+        let min_y = self.cells.iter().map(|cell| cell.coordinate().y()).min();
+        let max_x = self.cells.iter().map(|cell| cell.coordinate().x()).max();
+        let max_y = self.cells.iter().map(|cell| cell.coordinate().y()).max();
+
+        (
+            Coordinate::from((min_x.unwrap_or_default(), min_y.unwrap_or_default())),
+            Coordinate::from((max_x.unwrap_or_default(), max_y.unwrap_or_default())),
+        )
+    }
+
+    pub fn center(&self) -> Coordinate {
+        let (min, max) = self.bounds();
+        Coordinate::from(((min.x() + max.x()) / 2, (min.y() + max.y()) / 2))
+    }
+
     pub fn spawnable_cells(&self) -> Vec<&Cell> {
         self.cells
             .iter()
@@ -121,7 +170,7 @@ impl Room {
             return false;
         }
 
-        let surrounding = vec![
+        let surrounding = [
             (cell.coordinate().x() - 1, cell.coordinate().y()),
             (cell.coordinate().x(), cell.coordinate().y() - 1),
             (cell.coordinate().x() + 1, cell.coordinate().y()),
@@ -155,6 +204,16 @@ impl Room {
         }
     }
 
+    pub(crate) fn replace_cell(&mut self, replacement: &Cell) {
+        if let Some(cell) = self
+            .cells
+            .iter_mut()
+            .find(|cell| cell.coordinate() == replacement.coordinate())
+        {
+            *cell = replacement.clone();
+        }
+    }
+
     pub fn add_layer_to_cell(&mut self, x: i32, y: i32, layer: LayerType) {
         if let Some(cell) = self.cells.iter_mut().find(|cell| cell.is_at_location(x, y)) {
             cell.add_layer(layer);
@@ -164,9 +223,7 @@ impl Room {
     fn build_cell(x: i32, y: i32, c: char) -> Cell {
         let layer: LayerType = c.into();
         let mut cell = Cell::from((x, y));
-        if layer.ne(&LayerType::Floor(FloorType::Indoor))
-            && layer.ne(&LayerType::Structure(StructureType::Door))
-        {
+        if layer.ne(&LayerType::Floor(FloorType::Indoor)) && !layer.is_door() {
             cell.add_layer(LayerType::Floor(FloorType::Indoor));
         }
         cell.add_layer(layer);
@@ -192,6 +249,7 @@ mod tests {
                 LayerType::Floor(FloorType::Indoor),
             ))],
             max_side_length: 4,
+            door_policy: None,
         };
 
         let translated = room.translate(4, 6);
@@ -201,6 +259,40 @@ mod tests {
         assert_eq!(translated_cell.coordinate().y(), 7);
     }
 
+    #[test]
+    fn template_headers_set_the_door_policy() {
+        let room = Room::from("@door_count = 2..=3\n@secret_chance = 50\n||||\n|==|\n||||");
+
+        assert_eq!(
+            room,
+            Room::from("||||\n|==|\n||||").with_door_policy(room.door_policy().unwrap().clone())
+        );
+        assert_eq!(room.door_policy().unwrap().door_count, 2..=3);
+        assert_eq!(room.door_policy().unwrap().secret_chance, 50);
+        assert_eq!(room.max_side_length(), 4);
+        assert_eq!(
+            Room::from("@doors = 2\n||||\n|==|\n||||").door_policy(),
+            None
+        );
+        assert_eq!(
+            Room::from_template("@doors = 2\n||||\n|==|\n||||"),
+            Err("unknown door policy key doors".to_string())
+        );
+        assert_eq!(
+            Room::from_template("@door_count = 2..=3\n@secret_chance = 50\n||||\n|==|\n||||"),
+            Ok(room)
+        );
+    }
+
+    #[test]
+    fn only_leading_lines_are_a_template_header() {
+        let room = Room::from("@door_count = 2\n||||\n@==|\n||||");
+
+        assert_eq!(room.door_policy().unwrap().door_count, 2..=2);
+        assert_eq!(room.cells().len(), 12);
+        assert_eq!(room.max_side_length(), 4);
+    }
+
     #[test]
     fn from_template_string_builds_right_side_length() {
         let template_string = "wwww\nwffw\nwffd\nwwww".to_string();
@@ -1,11 +1,17 @@
-use super::{cells::Coordinate, Grid};
+use std::collections::HashSet;
 
-#[derive(Debug)]
+use serde::Serialize;
+
+use super::{cells::Coordinate, Grid, LockAndKey};
+
+#[derive(Debug, Serialize)]
 pub struct TopDownMap {
     grid: Grid,
     room_count: usize,
     entry_coordinate: Coordinate,
     exit_coordinate: Coordinate,
+    path: Option<Vec<Coordinate>>,
+    locks: Vec<LockAndKey>,
 }
 
 impl TopDownMap {
@@ -20,6 +26,8 @@ impl TopDownMap {
             room_count,
             entry_coordinate,
             exit_coordinate,
+            path: None,
+            locks: Vec::new(),
         }
     }
 
@@ -42,4 +50,71 @@ impl TopDownMap {
     pub fn exit(&self) -> &Coordinate {
         &self.exit_coordinate
     }
+
+    pub fn path(&self) -> Option<&Vec<Coordinate>> {
+        self.path.as_ref()
+    }
+
+    pub fn path_length(&self) -> Option<usize> {
+        self.path.as_ref().map(|path| path.len().saturating_sub(1))
+    }
+
+    pub fn set_path(&mut self, path: Option<Vec<Coordinate>>) {
+        self.path = path;
+    }
+
+    pub fn locks(&self) -> &Vec<LockAndKey> {
+        &self.locks
+    }
+
+    pub fn set_locks(&mut self, locks: Vec<LockAndKey>) {
+        self.locks = locks;
+    }
+
+    pub fn is_solvable(&self) -> bool {
+        let mut unlocked: HashSet<Coordinate> = HashSet::new();
+
+        loop {
+            let reachable = self.reachable_from_entry(&unlocked);
+            if reachable.contains(&self.exit_coordinate) {
+                return true;
+            }
+
+            let newly_unlocked: Vec<Coordinate> = self
+                .locks
+                .iter()
+                .filter(|lock_and_key| {
+                    reachable.contains(&lock_and_key.key) && !unlocked.contains(&lock_and_key.lock)
+                })
+                .map(|lock_and_key| lock_and_key.lock)
+                .collect();
+
+            if newly_unlocked.is_empty() {
+                return false;
+            }
+
+            unlocked.extend(newly_unlocked);
+        }
+    }
+
+    fn reachable_from_entry(&self, unlocked: &HashSet<Coordinate>) -> HashSet<Coordinate> {
+        let mut reachable: HashSet<Coordinate> = HashSet::from([self.entry_coordinate]);
+        let mut frontier = vec![self.entry_coordinate];
+
+        while let Some(coordinate) = frontier.pop() {
+            for neighbour in coordinate.neighbours() {
+                let passable = unlocked.contains(&neighbour)
+                    || self
+                        .grid
+                        .cell(&neighbour)
+                        .is_some_and(|cell| cell.is_walkable());
+
+                if passable && reachable.insert(neighbour) {
+                    frontier.push(neighbour);
+                }
+            }
+        }
+
+        reachable
+    }
 }
@@ -0,0 +1,285 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::map::{
+    cells::Coordinate,
+    layers::{FloorType, ItemRarity, LayerType, StructureType},
+    TopDownMap,
+};
+
+use super::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Glyph {
+    pub character: char,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Glyph {
+    pub fn new(character: char, foreground: Color) -> Self {
+        Self {
+            character,
+            foreground: Some(foreground),
+            background: None,
+        }
+    }
+
+    fn style(&self) -> String {
+        self.foreground
+            .iter()
+            .map(|color| color.foreground_code())
+            .chain(self.background.iter().map(|color| color.background_code()))
+            .collect::<Vec<String>>()
+            .join(";")
+    }
+}
+
+impl From<LayerType> for Glyph {
+    fn from(layer_type: LayerType) -> Self {
+        Self {
+            character: layer_type.into(),
+            foreground: None,
+            background: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnsiRenderer {
+    glyphs: HashMap<LayerType, Glyph>,
+    entrance_highlight: Option<Color>,
+    exit_highlight: Option<Color>,
+    path_highlight: Option<Color>,
+}
+
+const DEFAULT_GLYPHS: [(LayerType, u8); 23] = [
+    (LayerType::Empty, 236),
+    (LayerType::Entrance, 46),
+    (LayerType::Exit, 196),
+    (LayerType::Item(ItemRarity::Common), 255),
+    (LayerType::Item(ItemRarity::Uncommon), 40),
+    (LayerType::Item(ItemRarity::Rare), 33),
+    (LayerType::Item(ItemRarity::Exotic), 201),
+    (LayerType::Floor(FloorType::Indoor), 94),
+    (LayerType::Floor(FloorType::Outdoor), 22),
+    (LayerType::Key, 226),
+    (LayerType::Note, 229),
+    (LayerType::Path, 45),
+    (LayerType::Structure(StructureType::Boulder), 244),
+    (LayerType::Structure(StructureType::Door), 172),
+    (LayerType::Structure(StructureType::LockedDoor), 160),
+    (LayerType::Structure(StructureType::OpenDoor), 172),
+    (LayerType::Structure(StructureType::Other), 250),
+    (LayerType::Structure(StructureType::Rocks), 244),
+    (LayerType::Structure(StructureType::Rubble), 137),
+    (LayerType::Structure(StructureType::SecretDoor), 240),
+    (LayerType::Structure(StructureType::Table), 130),
+    (LayerType::Structure(StructureType::Wall), 250),
+    (LayerType::Structure(StructureType::Torch), 214),
+];
+
+impl Default for AnsiRenderer {
+    fn default() -> Self {
+        Self {
+            glyphs: DEFAULT_GLYPHS
+                .iter()
+                .map(|(layer_type, color)| {
+                    (
+                        *layer_type,
+                        Glyph::new((*layer_type).into(), Color::Indexed(*color)),
+                    )
+                })
+                .collect(),
+            entrance_highlight: Some(Color::Indexed(22)),
+            exit_highlight: Some(Color::Indexed(52)),
+            path_highlight: Some(Color::Indexed(17)),
+        }
+    }
+}
+
+impl AnsiRenderer {
+    pub fn plain() -> Self {
+        Self {
+            glyphs: HashMap::new(),
+            entrance_highlight: None,
+            exit_highlight: None,
+            path_highlight: None,
+        }
+    }
+
+    pub fn with_glyph(mut self, layer_type: LayerType, glyph: Glyph) -> Self {
+        self.glyphs.insert(layer_type, glyph);
+        self
+    }
+
+    pub fn with_entrance_highlight(mut self, color: Option<Color>) -> Self {
+        self.entrance_highlight = color;
+        self
+    }
+
+    pub fn with_exit_highlight(mut self, color: Option<Color>) -> Self {
+        self.exit_highlight = color;
+        self
+    }
+
+    pub fn with_path_highlight(mut self, color: Option<Color>) -> Self {
+        self.path_highlight = color;
+        self
+    }
+
+    pub fn glyph(&self, layer_type: &LayerType) -> Glyph {
+        self.glyphs
+            .get(layer_type)
+            .copied()
+            .unwrap_or_else(|| Glyph::from(*layer_type))
+    }
+
+    pub fn render(&self, map: &TopDownMap) -> String {
+        let size = map.grid().size() as i32;
+        self.render_area(map, -1..=size, -1..=size)
+    }
+
+    pub fn render_viewport(
+        &self,
+        map: &TopDownMap,
+        center: &Coordinate,
+        width: u16,
+        height: u16,
+    ) -> String {
+        let left = center.x() - width as i32 / 2;
+        let bottom = center.y() - height as i32 / 2;
+        self.render_area(
+            map,
+            left..=left + width as i32 - 1,
+            bottom..=bottom + height as i32 - 1,
+        )
+    }
+
+    fn render_area(
+        &self,
+        map: &TopDownMap,
+        columns: RangeInclusive<i32>,
+        rows: RangeInclusive<i32>,
+    ) -> String {
+        let path: HashSet<&Coordinate> = match (self.path_highlight, map.path()) {
+            (Some(_), Some(path)) => path.iter().collect(),
+            _ => HashSet::new(),
+        };
+        let mut text = String::new();
+
+        for y in rows.rev() {
+            let mut current_style = String::new();
+
+            for x in columns.clone() {
+                let coordinate = Coordinate::from((x, y));
+                let layer_type = map
+                    .grid()
+                    .cell(&coordinate)
+                    .map(|cell| cell.visible_layer())
+                    .unwrap_or_default();
+                let mut glyph = self.glyph(&layer_type);
+
+                let highlight = if coordinate == *map.entry() {
+                    self.entrance_highlight
+                } else if coordinate == *map.exit() {
+                    self.exit_highlight
+                } else if path.contains(&coordinate) {
+                    self.path_highlight
+                } else {
+                    None
+                };
+                if highlight.is_some() {
+                    glyph.background = highlight;
+                }
+
+                let style = glyph.style();
+                if style != current_style {
+                    if style.is_empty() {
+                        text.push_str("\x1b[0m");
+                    } else {
+                        text.push_str(&format!("\x1b[0;{}m", style));
+                    }
+                    current_style = style;
+                }
+                text.push(glyph.character);
+            }
+
+            if !current_style.is_empty() {
+                text.push_str("\x1b[0m");
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        map::layers::LayerType,
+        render::{Color, Glyph},
+        test_support::map,
+    };
+
+    use super::AnsiRenderer;
+
+    fn strip_escapes(text: &str) -> String {
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for character in text.chars() {
+            match character {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if in_escape => {}
+                _ => stripped.push(character),
+            }
+        }
+        stripped
+    }
+
+    #[test]
+    fn plain_rendering_matches_top_layer_display() {
+        let map = map("ansi", 30);
+
+        assert_eq!(
+            AnsiRenderer::plain().render(&map),
+            map.grid().top_layer_display()
+        );
+        assert_eq!(
+            strip_escapes(&AnsiRenderer::default().render(&map)),
+            map.grid().top_layer_display()
+        );
+    }
+
+    #[test]
+    fn entrance_and_custom_glyphs_are_styled() {
+        let map = map("ansi", 30);
+        let renderer = AnsiRenderer::default()
+            .with_glyph(
+                LayerType::Entrance,
+                Glyph::new('@', Color::Rgb(255, 255, 0)),
+            )
+            .with_entrance_highlight(Some(Color::Rgb(0, 0, 128)));
+
+        let text = renderer.render_viewport(&map, map.entry(), 1, 1);
+
+        assert_eq!(text, "\x1b[0;38;2;255;255;0;48;2;0;0;128m@\x1b[0m\n");
+    }
+
+    #[test]
+    fn viewport_is_centered_on_coordinate() {
+        let map = map("ansi", 30);
+        let text = strip_escapes(&AnsiRenderer::default().render_viewport(&map, map.exit(), 7, 5));
+        let rows: Vec<&str> = text.lines().collect();
+
+        assert_eq!(rows.len(), 5);
+        assert!(rows.iter().all(|row| row.chars().count() == 7));
+        assert_eq!(rows[2].chars().nth(3), Some('x'));
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Color {
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+const STANDARD_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    // Indexed colours follow the xterm 256 colour palette.
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Color::Rgb(red, green, blue) => (red, green, blue),
+            Color::Indexed(index @ 0..=15) => STANDARD_COLORS[index as usize],
+            Color::Indexed(index @ 16..=231) => {
+                let cube = index - 16;
+                (
+                    CUBE_LEVELS[(cube / 36) as usize],
+                    CUBE_LEVELS[(cube / 6 % 6) as usize],
+                    CUBE_LEVELS[(cube % 6) as usize],
+                )
+            }
+            Color::Indexed(index) => {
+                let gray = 8 + (index - 232) * 10;
+                (gray, gray, gray)
+            }
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        let (red, green, blue) = self.to_rgb();
+        format!("#{:02x}{:02x}{:02x}", red, green, blue)
+    }
+
+    pub fn foreground_code(&self) -> String {
+        match self {
+            Color::Indexed(index) => format!("38;5;{}", index),
+            Color::Rgb(red, green, blue) => format!("38;2;{};{};{}", red, green, blue),
+        }
+    }
+
+    pub fn background_code(&self) -> String {
+        match self {
+            Color::Indexed(index) => format!("48;5;{}", index),
+            Color::Rgb(red, green, blue) => format!("48;2;{};{};{}", red, green, blue),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+mod ansi_renderer;
+mod color;
+mod palette;
+#[cfg(feature = "png")]
+mod png_renderer;
+mod svg_renderer;
+
+pub use ansi_renderer::{AnsiRenderer, Glyph};
+pub use color::Color;
+#[cfg(feature = "png")]
+pub use png_renderer::{CellStyle, PngRenderer, Tile};
+pub use svg_renderer::SvgRenderer;
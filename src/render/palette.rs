@@ -0,0 +1,71 @@
+use crate::map::layers::{FloorType, ItemRarity, LayerType, StructureType};
+
+use super::Color;
+
+pub(crate) const DEFAULT_PALETTE: [(LayerType, Color); 23] = [
+    (LayerType::Empty, Color::Rgb(16, 16, 16)),
+    (LayerType::Entrance, Color::Rgb(60, 220, 90)),
+    (LayerType::Exit, Color::Rgb(220, 50, 50)),
+    (
+        LayerType::Item(ItemRarity::Common),
+        Color::Rgb(230, 230, 230),
+    ),
+    (
+        LayerType::Item(ItemRarity::Uncommon),
+        Color::Rgb(60, 200, 60),
+    ),
+    (LayerType::Item(ItemRarity::Rare), Color::Rgb(50, 120, 240)),
+    (
+        LayerType::Item(ItemRarity::Exotic),
+        Color::Rgb(230, 60, 230),
+    ),
+    (LayerType::Floor(FloorType::Indoor), Color::Rgb(120, 92, 62)),
+    (LayerType::Floor(FloorType::Outdoor), Color::Rgb(58, 94, 48)),
+    (LayerType::Key, Color::Rgb(250, 220, 40)),
+    (LayerType::Note, Color::Rgb(250, 240, 180)),
+    (LayerType::Path, Color::Rgb(70, 200, 230)),
+    (
+        LayerType::Structure(StructureType::Boulder),
+        Color::Rgb(110, 110, 110),
+    ),
+    (
+        LayerType::Structure(StructureType::Door),
+        Color::Rgb(200, 130, 40),
+    ),
+    (
+        LayerType::Structure(StructureType::LockedDoor),
+        Color::Rgb(200, 40, 40),
+    ),
+    (
+        LayerType::Structure(StructureType::OpenDoor),
+        Color::Rgb(200, 130, 40),
+    ),
+    (
+        LayerType::Structure(StructureType::Other),
+        Color::Rgb(180, 180, 180),
+    ),
+    (
+        LayerType::Structure(StructureType::Rocks),
+        Color::Rgb(130, 130, 120),
+    ),
+    (
+        LayerType::Structure(StructureType::Rubble),
+        Color::Rgb(150, 120, 90),
+    ),
+    (
+        LayerType::Structure(StructureType::SecretDoor),
+        Color::Rgb(150, 150, 150),
+    ),
+    (
+        LayerType::Structure(StructureType::Table),
+        Color::Rgb(160, 100, 40),
+    ),
+    (
+        LayerType::Structure(StructureType::Wall),
+        Color::Rgb(170, 170, 170),
+    ),
+    (
+        LayerType::Structure(StructureType::Torch),
+        Color::Rgb(255, 160, 40),
+    ),
+];
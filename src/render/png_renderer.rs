@@ -0,0 +1,420 @@
+use std::{collections::HashMap, fs::File, io::Write, num::NonZeroU8, path::Path};
+
+use crate::{
+    generation::GenerationError,
+    map::{
+        cells::Coordinate,
+        layers::{FloorType, LayerType, StructureType},
+        TopDownMap,
+    },
+};
+
+use super::{palette::DEFAULT_PALETTE, Color};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Solid,
+    Dot,
+    Frame,
+    Brick,
+    Cross,
+}
+
+impl Tile {
+    // Whether the pixel at (x, y) inside a cell of the given size is drawn in
+    // the tile's colour rather than left as the cell's floor.
+    fn covers(&self, x: u32, y: u32, size: u32) -> bool {
+        // Small cells can't fit the pattern, so they get the plain colour
+        // rather than disappearing into the floor.
+        let smallest = match self {
+            Tile::Dot => 3,
+            Tile::Brick => 4,
+            _ => 1,
+        };
+        if size < smallest {
+            return true;
+        }
+
+        let edge = (size / 4).max(1);
+        match self {
+            Tile::Solid => true,
+            Tile::Dot => (edge..size - edge).contains(&x) && (edge..size - edge).contains(&y),
+            Tile::Frame => x < edge || y < edge || x >= size - edge || y >= size - edge,
+            Tile::Brick => {
+                let course = (size / 2).max(1);
+                let mortar_x = if (y / course).is_multiple_of(2) {
+                    0
+                } else {
+                    size / 2
+                };
+                !y.is_multiple_of(course) && x != mortar_x
+            }
+            Tile::Cross => x.abs_diff(y) <= edge / 2 || (x + y).abs_diff(size - 1) <= edge / 2,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CellStyle {
+    pub color: Color,
+    pub tile: Tile,
+}
+
+#[derive(Clone, Debug)]
+pub struct PngRenderer {
+    styles: HashMap<LayerType, CellStyle>,
+    background: Color,
+    scale: NonZeroU8,
+    grid_lines: Option<Color>,
+    entrance: Option<Color>,
+    exit: Option<Color>,
+    path: Option<Color>,
+    room_bounds: Option<Color>,
+}
+
+fn default_tile(layer_type: &LayerType) -> Tile {
+    match layer_type {
+        LayerType::Item(_) | LayerType::Key | LayerType::Note | LayerType::Path => Tile::Dot,
+        LayerType::Structure(StructureType::Door | StructureType::LockedDoor) => Tile::Frame,
+        LayerType::Structure(
+            StructureType::OpenDoor | StructureType::Rocks | StructureType::Torch,
+        ) => Tile::Dot,
+        LayerType::Structure(StructureType::Wall | StructureType::SecretDoor) => Tile::Brick,
+        LayerType::Structure(
+            StructureType::Other | StructureType::Rubble | StructureType::Table,
+        ) => Tile::Cross,
+        _ => Tile::Solid,
+    }
+}
+
+impl Default for PngRenderer {
+    fn default() -> Self {
+        Self {
+            styles: DEFAULT_PALETTE
+                .iter()
+                .map(|(layer_type, color)| {
+                    (
+                        *layer_type,
+                        CellStyle {
+                            color: *color,
+                            tile: default_tile(layer_type),
+                        },
+                    )
+                })
+                .collect(),
+            background: Color::Rgb(16, 16, 16),
+            scale: NonZeroU8::new(8).unwrap(),
+            grid_lines: None,
+            entrance: Some(Color::Rgb(60, 220, 90)),
+            exit: Some(Color::Rgb(220, 50, 50)),
+            path: None,
+            room_bounds: None,
+        }
+    }
+}
+
+impl PngRenderer {
+    pub fn with_style(mut self, layer_type: LayerType, style: CellStyle) -> Self {
+        self.styles.insert(layer_type, style);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: NonZeroU8) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_grid_lines(mut self, color: Option<Color>) -> Self {
+        self.grid_lines = color;
+        self
+    }
+
+    pub fn with_entrance(mut self, color: Option<Color>) -> Self {
+        self.entrance = color;
+        self
+    }
+
+    pub fn with_exit(mut self, color: Option<Color>) -> Self {
+        self.exit = color;
+        self
+    }
+
+    pub fn with_path(mut self, color: Option<Color>) -> Self {
+        self.path = color;
+        self
+    }
+
+    pub fn with_room_bounds(mut self, color: Option<Color>) -> Self {
+        self.room_bounds = color;
+        self
+    }
+
+    pub fn style(&self, layer_type: &LayerType) -> CellStyle {
+        self.styles.get(layer_type).copied().unwrap_or(CellStyle {
+            color: self.background,
+            tile: Tile::Solid,
+        })
+    }
+
+    // Returns the width, height and RGB pixels of the image, top row first.
+    pub fn pixels(&self, map: &TopDownMap) -> (u32, u32, Vec<u8>) {
+        let (min, max) = map.grid().bounds();
+        let scale = self.scale.get() as u32;
+        let columns = (max.x() - min.x() + 1) as u32;
+        let rows = (max.y() - min.y() + 1) as u32;
+        let (red, green, blue) = self.background.to_rgb();
+        let mut image = Image {
+            width: columns * scale,
+            height: rows * scale,
+            pixels: [red, green, blue].repeat((columns * scale * rows * scale) as usize),
+        };
+
+        // Pixel rows count down from the top of the map, so y is flipped.
+        let origin = |coordinate: &Coordinate| {
+            (
+                (coordinate.x() - min.x()) as u32 * scale,
+                (max.y() - coordinate.y()) as u32 * scale,
+            )
+        };
+
+        for cell in map.grid().cells() {
+            let (left, top) = origin(cell.coordinate());
+            let layer_type = cell.visible_layer();
+            let style = self.style(&layer_type);
+            let floor = match cell.floor_type() {
+                Some(FloorType::Indoor) => self.style(&LayerType::Floor(FloorType::Indoor)),
+                Some(FloorType::Outdoor) => self.style(&LayerType::Floor(FloorType::Outdoor)),
+                None => CellStyle {
+                    color: self.background,
+                    tile: Tile::Solid,
+                },
+            };
+
+            for y in 0..scale {
+                for x in 0..scale {
+                    let color = if style.tile.covers(x, y, scale) {
+                        style.color
+                    } else {
+                        floor.color
+                    };
+                    image.set(left + x, top + y, color);
+                }
+            }
+        }
+
+        if let (Some(color), Some(path)) = (self.path, map.path()) {
+            for coordinate in path.iter() {
+                let (left, top) = origin(coordinate);
+                image.fill(left, top, scale, Tile::Dot, color);
+            }
+        }
+
+        for (color, coordinate) in [(self.entrance, map.entry()), (self.exit, map.exit())] {
+            if let Some(color) = color {
+                let (left, top) = origin(coordinate);
+                image.fill(left, top, scale, Tile::Solid, color);
+            }
+        }
+
+        if let Some(color) = self.grid_lines {
+            for y in 0..image.height {
+                for x in 0..image.width {
+                    if x % scale == scale - 1 || y % scale == scale - 1 {
+                        image.set(x, y, color);
+                    }
+                }
+            }
+        }
+
+        if let Some(color) = self.room_bounds {
+            for room in map.grid().rooms() {
+                let (room_min, room_max) = room.bounds();
+                let (left, bottom) = origin(&room_min);
+                let (right, top) = origin(&room_max);
+                let (right, bottom) = (right + scale - 1, bottom + scale - 1);
+
+                for x in left..=right {
+                    image.set(x, top, color);
+                    image.set(x, bottom, color);
+                }
+                for y in top..=bottom {
+                    image.set(left, y, color);
+                    image.set(right, y, color);
+                }
+            }
+        }
+
+        (image.width, image.height, image.pixels)
+    }
+
+    pub fn write_png<W: Write>(&self, map: &TopDownMap, writer: W) -> Result<(), GenerationError> {
+        let (width, height, pixels) = self.pixels(map);
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|error| GenerationError::map_cannot_be_written(&error.to_string()))
+    }
+
+    pub fn save(&self, map: &TopDownMap, path: impl AsRef<Path>) -> Result<(), GenerationError> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|error| {
+            GenerationError::map_cannot_be_written(&format!("{}: {}", path.display(), error))
+        })?;
+
+        self.write_png(map, file)
+    }
+}
+
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn set(&mut self, x: u32, y: u32, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let index = ((y * self.width + x) * 3) as usize;
+        let (red, green, blue) = color.to_rgb();
+        self.pixels[index..index + 3].copy_from_slice(&[red, green, blue]);
+    }
+
+    fn fill(&mut self, left: u32, top: u32, size: u32, tile: Tile, color: Color) {
+        for y in 0..size {
+            for x in 0..size {
+                if tile.covers(x, y, size) {
+                    self.set(left + x, top + y, color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU16, NonZeroU8};
+
+    use crate::{
+        generation::{builder, ItemChance, ItemGeneration},
+        map::layers::{ItemRarity, LayerType, StructureType},
+        render::{Color, PngRenderer},
+        test_support::map,
+    };
+
+    fn pixel(pixels: &[u8], width: u32, x: u32, y: u32) -> (u8, u8, u8) {
+        let index = ((y * width + x) * 3) as usize;
+        (pixels[index], pixels[index + 1], pixels[index + 2])
+    }
+
+    #[test]
+    fn encodes_scaled_png_with_overlays() {
+        let map = map("png", 20);
+        let renderer = PngRenderer::default()
+            .with_scale(NonZeroU8::new(4).unwrap())
+            .with_entrance(Some(Color::Rgb(1, 2, 3)))
+            .with_grid_lines(Some(Color::Rgb(9, 9, 9)));
+
+        let mut bytes: Vec<u8> = Vec::new();
+        renderer.write_png(&map, &mut bytes).unwrap();
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+
+        // 20 cells plus the outer wall on both sides, four pixels each.
+        assert_eq!((info.width, info.height), (88, 88));
+
+        let x = (map.entry().x() + 1) as u32 * 4;
+        let y = (20 - map.entry().y()) as u32 * 4;
+        assert_eq!(pixel(&pixels, 88, x, y), (1, 2, 3));
+        assert_eq!(pixel(&pixels, 88, x + 3, y), (9, 9, 9));
+    }
+
+    #[test]
+    fn room_bounds_are_outlined() {
+        let map = map("png", 20);
+        let renderer = PngRenderer::default()
+            .with_scale(NonZeroU8::new(2).unwrap())
+            .with_room_bounds(Some(Color::Indexed(201)));
+
+        let (width, _, pixels) = renderer.pixels(&map);
+        let (min, max) = map.grid().rooms()[0].bounds();
+
+        assert_eq!(
+            pixel(
+                &pixels,
+                width,
+                (min.x() + 1) as u32 * 2,
+                (20 - max.y()) as u32 * 2
+            ),
+            (255, 0, 255)
+        );
+    }
+
+    #[test]
+    fn patterns_fall_back_to_solid_at_small_scales() {
+        let map = builder()
+            .seed("png")
+            .grid_size(NonZeroU16::new(20).unwrap())
+            .include_outer_wall(true)
+            .target_items(ItemGeneration {
+                target_num_items: 5,
+                item_ranges: vec![ItemChance {
+                    layer_type: LayerType::Item(ItemRarity::Common),
+                    chance: 0..100,
+                }],
+            })
+            .build()
+            .generate_top_down_map()
+            .unwrap();
+        let find = |layer_type: LayerType| {
+            *map.grid()
+                .cells()
+                .into_iter()
+                .find(|cell| cell.visible_layer() == layer_type)
+                .unwrap()
+                .coordinate()
+        };
+        let wall = find(LayerType::Structure(StructureType::Wall));
+        let item = find(LayerType::Item(ItemRarity::Common));
+
+        for scale in 1..=3 {
+            let renderer = PngRenderer::default().with_scale(NonZeroU8::new(scale).unwrap());
+            let (width, _, pixels) = renderer.pixels(&map);
+            let scale = scale as u32;
+            let at = |coordinate: &crate::map::cells::Coordinate| {
+                pixel(
+                    &pixels,
+                    width,
+                    (coordinate.x() + 1) as u32 * scale,
+                    (20 - coordinate.y()) as u32 * scale,
+                )
+            };
+
+            assert_eq!(
+                at(&wall),
+                renderer
+                    .style(&LayerType::Structure(StructureType::Wall))
+                    .color
+                    .to_rgb()
+            );
+            if scale < 3 {
+                assert_eq!(
+                    at(&item),
+                    renderer
+                        .style(&LayerType::Item(ItemRarity::Common))
+                        .color
+                        .to_rgb()
+                );
+            }
+        }
+    }
+}
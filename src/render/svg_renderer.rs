@@ -0,0 +1,272 @@
+use std::{collections::HashMap, fmt::Write, fs, num::NonZeroU16, path::Path};
+
+use crate::{
+    generation::GenerationError,
+    map::{cells::Coordinate, layers::LayerType, TopDownMap},
+};
+
+use super::{palette::DEFAULT_PALETTE, Color};
+
+#[derive(Clone, Debug)]
+pub struct SvgRenderer {
+    colors: HashMap<LayerType, Color>,
+    cell_size: NonZeroU16,
+    rooms: Option<Color>,
+    path: Option<Color>,
+    entrance: Option<Color>,
+    exit: Option<Color>,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self {
+            colors: DEFAULT_PALETTE.iter().copied().collect(),
+            cell_size: NonZeroU16::new(10).unwrap(),
+            rooms: Some(Color::Rgb(255, 255, 255)),
+            path: Some(Color::Rgb(70, 200, 230)),
+            entrance: Some(Color::Rgb(60, 220, 90)),
+            exit: Some(Color::Rgb(220, 50, 50)),
+        }
+    }
+}
+
+impl SvgRenderer {
+    pub fn with_color(mut self, layer_type: LayerType, color: Color) -> Self {
+        self.colors.insert(layer_type, color);
+        self
+    }
+
+    pub fn with_cell_size(mut self, cell_size: NonZeroU16) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn with_rooms(mut self, color: Option<Color>) -> Self {
+        self.rooms = color;
+        self
+    }
+
+    pub fn with_path(mut self, color: Option<Color>) -> Self {
+        self.path = color;
+        self
+    }
+
+    pub fn with_entrance(mut self, color: Option<Color>) -> Self {
+        self.entrance = color;
+        self
+    }
+
+    pub fn with_exit(mut self, color: Option<Color>) -> Self {
+        self.exit = color;
+        self
+    }
+
+    pub fn render(&self, map: &TopDownMap) -> String {
+        let (min, max) = map.grid().bounds();
+        let size = self.cell_size.get() as i32;
+        let width = (max.x() - min.x() + 1) * size;
+        let height = (max.y() - min.y() + 1) * size;
+        // SVG's y axis points down, so the top row of the map is max.y.
+        let corner = |coordinate: &Coordinate| {
+            (
+                (coordinate.x() - min.x()) * size,
+                (max.y() - coordinate.y()) * size,
+            )
+        };
+        let center = |coordinate: &Coordinate| {
+            let (x, y) = corner(coordinate);
+            (x + size / 2, y + size / 2)
+        };
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )
+        .unwrap();
+
+        let mut cells_by_layer: Vec<(LayerType, Vec<Coordinate>)> = Vec::new();
+        for cell in map.grid().cells() {
+            let layer_type = cell.visible_layer();
+            match cells_by_layer
+                .iter_mut()
+                .find(|(existing, _)| *existing == layer_type)
+            {
+                Some((_, coordinates)) => coordinates.push(*cell.coordinate()),
+                None => cells_by_layer.push((layer_type, vec![*cell.coordinate()])),
+            }
+        }
+        cells_by_layer.sort_by_key(|(layer_type, _)| layer_order(layer_type));
+
+        for (layer_type, mut coordinates) in cells_by_layer {
+            coordinates.sort();
+            let color = self
+                .colors
+                .get(&layer_type)
+                .copied()
+                .unwrap_or(Color::Rgb(0, 0, 0));
+
+            writeln!(
+                svg,
+                r#"<g class="{}" fill="{}">"#,
+                layer_class(&layer_type),
+                color.to_hex()
+            )
+            .unwrap();
+            for coordinate in coordinates {
+                let (x, y) = corner(&coordinate);
+                writeln!(
+                    svg,
+                    r#"<rect x="{x}" y="{y}" width="{size}" height="{size}"/>"#
+                )
+                .unwrap();
+            }
+            writeln!(svg, "</g>").unwrap();
+        }
+
+        if let Some(color) = self.rooms {
+            writeln!(
+                svg,
+                r#"<g class="rooms" fill="none" stroke="{}" font-size="{}" font-family="sans-serif">"#,
+                color.to_hex(),
+                size
+            )
+            .unwrap();
+            for (index, room) in map.grid().rooms().iter().enumerate() {
+                let (room_min, room_max) = room.bounds();
+                let (left, _) = corner(&room_min);
+                let (_, top) = corner(&room_max);
+                let room_width = (room_max.x() - room_min.x() + 1) * size;
+                let room_height = (room_max.y() - room_min.y() + 1) * size;
+
+                writeln!(
+                    svg,
+                    r#"<rect x="{left}" y="{top}" width="{room_width}" height="{room_height}"/>"#
+                )
+                .unwrap();
+                writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" fill="{}" stroke="none">room {}</text>"#,
+                    left + size,
+                    top + size * 2,
+                    color.to_hex(),
+                    index
+                )
+                .unwrap();
+            }
+            writeln!(svg, "</g>").unwrap();
+        }
+
+        if let (Some(color), Some(path)) = (self.path, map.path()) {
+            let points: Vec<String> = path
+                .iter()
+                .map(|coordinate| {
+                    let (x, y) = center(coordinate);
+                    format!("{},{}", x, y)
+                })
+                .collect();
+            writeln!(
+                svg,
+                r#"<polyline class="path" points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                points.join(" "),
+                color.to_hex(),
+                (size / 4).max(1)
+            )
+            .unwrap();
+        }
+
+        for (class, color, coordinate) in [
+            ("entrance", self.entrance, map.entry()),
+            ("exit", self.exit, map.exit()),
+        ] {
+            if let Some(color) = color {
+                let (x, y) = center(coordinate);
+                writeln!(
+                    svg,
+                    r#"<circle class="{}" cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    class,
+                    x,
+                    y,
+                    size,
+                    color.to_hex()
+                )
+                .unwrap();
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn save(&self, map: &TopDownMap, path: impl AsRef<Path>) -> Result<(), GenerationError> {
+        let path = path.as_ref();
+        fs::write(path, self.render(map)).map_err(|error| {
+            GenerationError::map_cannot_be_written(&format!("{}: {}", path.display(), error))
+        })
+    }
+}
+
+fn layer_order(layer_type: &LayerType) -> usize {
+    DEFAULT_PALETTE
+        .iter()
+        .position(|(known, _)| known == layer_type)
+        .unwrap_or(DEFAULT_PALETTE.len())
+}
+
+// Item(Rare) becomes "item-rare" and Structure(LockedDoor) "structure-locked-door".
+fn layer_class(layer_type: &LayerType) -> String {
+    let mut class = String::new();
+    for character in format!("{:?}", layer_type).chars() {
+        match character {
+            '(' => class.push('-'),
+            ')' => {}
+            _ if character.is_uppercase() => {
+                if !class.is_empty() && !class.ends_with('-') {
+                    class.push('-');
+                }
+                class.extend(character.to_lowercase());
+            }
+            _ => class.push(character),
+        }
+    }
+    class
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::builder,
+        map::layers::{LayerType, StructureType},
+    };
+
+    use super::{layer_class, SvgRenderer};
+
+    #[test]
+    fn layer_classes_are_kebab_case() {
+        assert_eq!(
+            layer_class(&LayerType::Structure(StructureType::LockedDoor)),
+            "structure-locked-door"
+        );
+        assert_eq!(layer_class(&LayerType::Entrance), "entrance");
+    }
+
+    #[test]
+    fn matches_golden_svgs() {
+        for (seed, golden) in [
+            ("svg one", include_str!("../../assets/golden/svg_one.svg")),
+            ("svg two", include_str!("../../assets/golden/svg_two.svg")),
+        ] {
+            let map = builder()
+                .seed(seed)
+                .grid_size(NonZeroU16::new(24).unwrap())
+                .target_number_rooms(NonZeroU16::new(6).unwrap())
+                .build()
+                .generate_top_down_map()
+                .unwrap();
+
+            assert_eq!(SvgRenderer::default().render(&map), golden, "{}", seed);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::num::NonZeroU16;
+
+use crate::{
+    generation::{builder, GeneratorBuilder},
+    map::{Grid, TopDownMap},
+};
+
+// A grid with every cell filled in and no rooms, walls or items.
+pub fn open_grid(size: u16) -> Grid {
+    let mut grid = Grid::build(size, "open".to_string());
+    grid.fill_empty_cells();
+    grid
+}
+
+// A generated map with an outer wall, anything else is set by `configure`.
+pub fn map_with(
+    seed: &str,
+    size: u16,
+    configure: impl FnOnce(&mut GeneratorBuilder),
+) -> TopDownMap {
+    let mut builder = builder();
+    builder
+        .seed(seed)
+        .grid_size(NonZeroU16::new(size).unwrap())
+        .include_outer_wall(true);
+    configure(&mut builder);
+    builder.build().generate_top_down_map().unwrap()
+}
+
+pub fn map(seed: &str, size: u16) -> TopDownMap {
+    map_with(seed, size, |_| {})
+}
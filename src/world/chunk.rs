@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use crate::map::{
+    cells::{Cell, Coordinate},
+    Room,
+};
+
+#[derive(Clone, Debug)]
+pub struct Chunk {
+    coordinate: Coordinate,
+    cells: HashMap<Coordinate, Cell>,
+    rooms: Vec<Room>,
+}
+
+impl Chunk {
+    pub(crate) fn new(coordinate: Coordinate, cells: Vec<Cell>, rooms: Vec<Room>) -> Self {
+        Self {
+            coordinate,
+            cells: cells
+                .into_iter()
+                .map(|cell| (*cell.coordinate(), cell))
+                .collect(),
+            rooms,
+        }
+    }
+
+    pub fn coordinate(&self) -> &Coordinate {
+        &self.coordinate
+    }
+
+    pub fn cell(&self, coordinate: &Coordinate) -> Option<&Cell> {
+        self.cells.get(coordinate)
+    }
+
+    pub fn cells(&self) -> Vec<&Cell> {
+        self.cells.values().collect()
+    }
+
+    pub fn rooms(&self) -> &Vec<Room> {
+        &self.rooms
+    }
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroU16,
+};
+
+use rand::Rng;
+
+use crate::{
+    generation::{assets::RoomTemplates, place_doors, GenerationError, SeedVersion},
+    map::{
+        cells::{Cell, Coordinate},
+        DoorPolicy, Room,
+    },
+};
+
+use super::Chunk;
+
+#[derive(Debug)]
+pub struct ChunkedWorld {
+    seed: String,
+    seed_version: SeedVersion,
+    chunk_size: NonZeroU16,
+    rooms_per_chunk: u16,
+    room_templates: RoomTemplates,
+    door_policy: DoorPolicy,
+    chunks: HashMap<Coordinate, Chunk>,
+    candidate_rooms: HashMap<Coordinate, Vec<Room>>,
+}
+
+impl ChunkedWorld {
+    pub fn new(
+        seed: &str,
+        chunk_size: NonZeroU16,
+        rooms_per_chunk: u16,
+        room_templates: RoomTemplates,
+    ) -> Result<Self, GenerationError> {
+        if room_templates.rooms.is_empty() {
+            return Err(GenerationError::no_room_paths());
+        }
+
+        if room_templates
+            .rooms
+            .iter()
+            .any(|room| room.max_side_length() > chunk_size.get())
+        {
+            return Err(GenerationError::room_templates_too_large());
+        }
+
+        Ok(Self {
+            seed: seed.to_string(),
+            seed_version: SeedVersion::default(),
+            chunk_size,
+            rooms_per_chunk,
+            room_templates,
+            door_policy: DoorPolicy::default(),
+            chunks: HashMap::new(),
+            candidate_rooms: HashMap::new(),
+        })
+    }
+
+    pub fn with_door_policy(mut self, door_policy: DoorPolicy) -> Self {
+        self.door_policy = door_policy;
+        self
+    }
+
+    pub fn with_seed_version(mut self, seed_version: SeedVersion) -> Self {
+        self.seed_version = seed_version;
+        self
+    }
+
+    pub fn seed(&self) -> &str {
+        &self.seed
+    }
+
+    pub fn seed_version(&self) -> SeedVersion {
+        self.seed_version
+    }
+
+    pub fn chunk_size(&self) -> NonZeroU16 {
+        self.chunk_size
+    }
+
+    pub fn chunk_coordinate(&self, coordinate: &Coordinate) -> Coordinate {
+        let size = self.chunk_size.get() as i32;
+        Coordinate::from((
+            coordinate.x().div_euclid(size),
+            coordinate.y().div_euclid(size),
+        ))
+    }
+
+    pub fn cell(&mut self, coordinate: &Coordinate) -> &Cell {
+        let chunk_coordinate = self.chunk_coordinate(coordinate);
+        self.chunk(&chunk_coordinate).cell(coordinate).unwrap()
+    }
+
+    pub fn chunk(&mut self, chunk_coordinate: &Coordinate) -> &Chunk {
+        if !self.chunks.contains_key(chunk_coordinate) {
+            let chunk = self.generate_chunk(chunk_coordinate);
+            self.chunks.insert(*chunk_coordinate, chunk);
+        }
+
+        self.chunks.get(chunk_coordinate).unwrap()
+    }
+
+    pub fn loaded_chunk(&self, chunk_coordinate: &Coordinate) -> Option<&Chunk> {
+        self.chunks.get(chunk_coordinate)
+    }
+
+    pub fn loaded_chunks(&self) -> Vec<Coordinate> {
+        let mut coordinates: Vec<Coordinate> = self.chunks.keys().copied().collect();
+        coordinates.sort();
+        coordinates
+    }
+
+    pub fn evict(&mut self, chunk_coordinate: &Coordinate) -> Option<Chunk> {
+        let chunk = self.chunks.remove(chunk_coordinate);
+        let loaded: Vec<Coordinate> = self.chunks.keys().copied().collect();
+
+        self.candidate_rooms.retain(|coordinate, _| {
+            loaded.iter().any(|chunk_coordinate| {
+                coordinate.x().abs_diff(chunk_coordinate.x()) <= 2
+                    && coordinate.y().abs_diff(chunk_coordinate.y()) <= 2
+            })
+        });
+
+        chunk
+    }
+
+    fn generate_chunk(&mut self, chunk_coordinate: &Coordinate) -> Chunk {
+        let size = self.chunk_size.get() as i32;
+        let origin = Coordinate::from((chunk_coordinate.x() * size, chunk_coordinate.y() * size));
+
+        let mut cells: HashMap<Coordinate, Cell> = HashMap::new();
+        for x in origin.x()..origin.x() + size {
+            for y in origin.y()..origin.y() + size {
+                let mut cell = Cell::from((x, y));
+                cell.set_to_floor();
+                cells.insert(*cell.coordinate(), cell);
+            }
+        }
+
+        // Rooms only grow up and to the right from where they are placed, so
+        // anything covering this chunk is owned by it or the chunks below/left.
+        let mut owned_rooms: Vec<Room> = Vec::new();
+        for (dx, dy) in [(-1, -1), (-1, 0), (0, -1), (0, 0)] {
+            let owner = Coordinate::from((chunk_coordinate.x() + dx, chunk_coordinate.y() + dy));
+            let rooms = self.kept_rooms(&owner);
+
+            for room in rooms.iter() {
+                for room_cell in room.cells() {
+                    if let Some(cell) = cells.get_mut(room_cell.coordinate()) {
+                        cell.clear_contents();
+                        for layer in room_cell.layers() {
+                            cell.add_layer(*layer);
+                        }
+                    }
+                }
+            }
+
+            if owner == *chunk_coordinate {
+                owned_rooms = rooms;
+            }
+        }
+
+        Chunk::new(
+            *chunk_coordinate,
+            cells.into_values().collect(),
+            owned_rooms,
+        )
+    }
+
+    // A room survives unless it overlaps a room owned by a neighbouring chunk
+    // that sorts before its own chunk. That only depends on the seed, so chunks
+    // agree on the rooms along their borders whatever order they load in.
+    fn kept_rooms(&mut self, owner: &Coordinate) -> Vec<Room> {
+        let mut claimed: HashSet<Coordinate> = HashSet::new();
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let neighbour = Coordinate::from((owner.x() + dx, owner.y() + dy));
+                if neighbour < *owner {
+                    for room in self.candidate_rooms(&neighbour) {
+                        claimed.extend(room.cells().iter().map(|cell| *cell.coordinate()));
+                    }
+                }
+            }
+        }
+
+        self.candidate_rooms(owner)
+            .into_iter()
+            .filter(|room| {
+                room.cells()
+                    .iter()
+                    .all(|cell| !claimed.contains(cell.coordinate()))
+            })
+            .collect()
+    }
+
+    fn candidate_rooms(&mut self, chunk_coordinate: &Coordinate) -> Vec<Room> {
+        if let Some(rooms) = self.candidate_rooms.get(chunk_coordinate) {
+            return rooms.clone();
+        }
+
+        let size = self.chunk_size.get() as i32;
+        let mut rng = self.seed_version.chunk_rng(&self.seed, chunk_coordinate);
+
+        let mut rooms: Vec<Room> = Vec::new();
+        let mut occupied: HashSet<Coordinate> = HashSet::new();
+
+        for _ in 0..self.rooms_per_chunk {
+            let index: usize = rng.gen_range(0..self.room_templates.rooms.len());
+            let template = &self.room_templates.rooms[index];
+            let x = chunk_coordinate.x() * size + rng.gen_range(0..size);
+            let y = chunk_coordinate.y() * size + rng.gen_range(0..size);
+            let mut room = template.translate(x, y);
+
+            let door_policy = template
+                .door_policy()
+                .cloned()
+                .unwrap_or_else(|| self.door_policy.clone());
+            if !(door_policy.keep_template_doors && room.has_doors())
+                && place_doors(&mut room, &door_policy, &rooms, &mut rng).is_none()
+            {
+                continue;
+            }
+
+            if room
+                .cells()
+                .iter()
+                .all(|cell| !occupied.contains(cell.coordinate()))
+            {
+                occupied.extend(room.cells().iter().map(|cell| *cell.coordinate()));
+                rooms.push(room);
+            }
+        }
+
+        self.candidate_rooms
+            .insert(*chunk_coordinate, rooms.clone());
+        rooms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use crate::{
+        generation::{assets::RoomTemplates, SeedVersion},
+        map::cells::Coordinate,
+    };
+
+    use super::ChunkedWorld;
+
+    fn world() -> ChunkedWorld {
+        ChunkedWorld::new(
+            "overworld",
+            NonZeroU16::new(16).unwrap(),
+            6,
+            RoomTemplates::default(),
+        )
+        .unwrap()
+    }
+
+    fn layers_in(world: &mut ChunkedWorld, chunk: Coordinate) -> Vec<String> {
+        let mut cells: Vec<(Coordinate, String)> = world
+            .chunk(&chunk)
+            .cells()
+            .iter()
+            .map(|cell| (*cell.coordinate(), format!("{:?}", cell.layers())))
+            .collect();
+        cells.sort();
+        cells.into_iter().map(|(_, layers)| layers).collect()
+    }
+
+    #[test]
+    fn chunks_do_not_depend_on_generation_order() {
+        let mut forward = world();
+        let mut backward = world();
+        let chunks: Vec<Coordinate> = (-2..=2)
+            .flat_map(|x| (-2..=2).map(move |y| Coordinate::from((x, y))))
+            .collect();
+
+        let forward_layers: Vec<Vec<String>> = chunks
+            .iter()
+            .map(|chunk| layers_in(&mut forward, *chunk))
+            .collect();
+        let mut backward_layers: Vec<Vec<String>> = chunks
+            .iter()
+            .rev()
+            .map(|chunk| layers_in(&mut backward, *chunk))
+            .collect();
+        backward_layers.reverse();
+
+        assert_eq!(forward_layers, backward_layers);
+    }
+
+    #[test]
+    fn rooms_straddle_chunk_borders() {
+        let mut world = world();
+        let straddling = (-3..=3)
+            .flat_map(|x| (-3..=3).map(move |y| Coordinate::from((x, y))))
+            .flat_map(|chunk| world.chunk(&chunk).rooms().clone())
+            .filter(|room| {
+                let (min, max) = room.bounds();
+                min.x().div_euclid(16) != max.x().div_euclid(16)
+                    || min.y().div_euclid(16) != max.y().div_euclid(16)
+            })
+            .count();
+
+        assert!(straddling > 0);
+    }
+
+    #[test]
+    fn cells_are_fetched_by_global_coordinate() {
+        let mut world = world();
+        let coordinate = Coordinate::from((-5, 37));
+
+        assert_eq!(world.cell(&coordinate).coordinate(), &coordinate);
+        assert_eq!(world.loaded_chunks(), vec![Coordinate::from((-1, 2))]);
+    }
+
+    #[test]
+    fn seed_version_picks_the_chunk_rng() {
+        let chunk = Coordinate::from((2, -1));
+        let legacy = layers_in(&mut world(), chunk);
+        let derived = layers_in(&mut world().with_seed_version(SeedVersion::V2), chunk);
+
+        assert_ne!(legacy, derived);
+        assert_eq!(
+            layers_in(&mut world().with_seed_version(SeedVersion::V2), chunk),
+            derived
+        );
+    }
+
+    #[test]
+    fn evicted_chunks_regenerate_identically() {
+        let mut world = world();
+        let chunk = Coordinate::from((3, -4));
+        let before = layers_in(&mut world, chunk);
+
+        assert!(world.evict(&chunk).is_some());
+        assert!(world.loaded_chunk(&chunk).is_none());
+        assert_eq!(layers_in(&mut world, chunk), before);
+    }
+}
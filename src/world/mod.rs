@@ -0,0 +1,5 @@
+mod chunk;
+mod chunked_world;
+
+pub use chunk::Chunk;
+pub use chunked_world::ChunkedWorld;